@@ -10,7 +10,7 @@ pub fn struct_from_tsv(_input: TokenStream) -> TokenStream {
 
     proc_macro::TokenStream::from(
         quote! {
-            #[derive(Debug, serde::Deserialize, Clone)]
+            #[derive(Debug, serde::Deserialize, Clone, Default)]
             pub struct #name {
                 #(pub #input : String),*
             }
@@ -0,0 +1,170 @@
+use base64::Engine;
+use minijinja::Value;
+use serde::Serialize;
+use sha2::{Digest, Sha384};
+use crate::templates;
+
+// Where pages get their CSS and JS from. Anything but the CDN is written to docs/assets and linked relatively,
+// so the site works offline (e.g. from a USB stick at a venue) and nothing is fetched from third parties.
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+pub enum Assets {
+    #[default]
+    Cdn,
+    Bundled, // Bootstrap and its icons, downloaded once when the site is built
+    Minimal, // A small stylesheet and script built into the binary, with no icon font
+}
+
+const BOOTSTRAP: &str = "https://cdn.jsdelivr.net/npm/bootstrap@5.3.1/dist";
+const ICONS: &str = "https://cdn.jsdelivr.net/npm/bootstrap-icons@1.10.5/font";
+const BOOTSTRAP_JS_INTEGRITY: &str = "sha384-HwwvtgBNo3bZJJLYd8oVXjrBZt8cqVSpeBNS5n7C8IVInixGAoxmnlMuBnhbgrkm";
+
+// (file in docs/assets, where it's downloaded from)
+const DOWNLOADS: [(&str, &str, &str); 5] = [
+    ("bootstrap.min.css", BOOTSTRAP, "css/bootstrap.min.css"),
+    ("bootstrap.bundle.min.js", BOOTSTRAP, "js/bootstrap.bundle.min.js"),
+    ("bootstrap-icons.css", ICONS, "bootstrap-icons.css"),
+    ("fonts/bootstrap-icons.woff2", ICONS, "fonts/bootstrap-icons.woff2"),
+    ("fonts/bootstrap-icons.woff", ICONS, "fonts/bootstrap-icons.woff"),
+];
+
+const MINIMAL: [(&str, &str); 2] = [
+    ("minimal.css", include_str!("assets/minimal.css")),
+    ("minimal.js", include_str!("assets/minimal.js")),
+];
+
+#[derive(Serialize)]
+pub struct Link {
+    url: Value, // Ours rather than the data's, so left unescaped
+    integrity: Option<&'static str>,
+}
+
+// What page.html and homepage.html put in their <head>
+#[derive(Serialize)]
+pub struct Links {
+    styles: Vec<Link>,
+    scripts: Vec<Link>,
+}
+
+fn url(file: &str) -> String {
+    DOWNLOADS
+        .iter()
+        .find(|(f, _, _)| *f == file)
+        .map(|(_, base, path)| format!("{base}/{path}"))
+        .unwrap_or_default()
+}
+
+// Only the files the CDN links pin with an integrity hash
+fn integrity(file: &str) -> Option<&'static str> {
+    (file == "bootstrap.bundle.min.js").then_some(BOOTSTRAP_JS_INTEGRITY)
+}
+
+// Integrity hashes are "sha384-" then the base64 of the file's SHA-384
+fn matches_integrity(bytes: &[u8], integrity: &str) -> bool {
+    integrity.strip_prefix("sha384-") == Some(base64::engine::general_purpose::STANDARD.encode(Sha384::digest(bytes)).as_str())
+}
+
+// The icon CSS asks for its fonts with a cache busting query, which doesn't work for files opened straight from disk
+fn strip_font_queries(css: &str) -> String {
+    let mut parts = css.split('?');
+    let mut out = parts.next().unwrap_or_default().to_string();
+    for part in parts {
+        if out.ends_with(".woff") || out.ends_with(".woff2") {
+            out += part.trim_start_matches(|c: char| c.is_ascii_alphanumeric());
+        }
+        else {
+            out += "?";
+            out += part;
+        }
+    }
+    out
+}
+
+impl Assets {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "cdn" => Some(Assets::Cdn),
+            "bundle" => Some(Assets::Bundled),
+            "minimal" => Some(Assets::Minimal),
+            _ => None,
+        }
+    }
+
+    // to_docs is the way from the page to docs/, e.g. "" for pages and "docs/" for the homepage
+    pub fn links(&self, to_docs: &str) -> Links {
+        let link = |url: String, integrity| Link { url: templates::html(url), integrity };
+        let local = |file: &str| link(format!("{to_docs}assets/{file}"), None);
+
+        match self {
+            Assets::Cdn => Links {
+                styles: vec![link(url("bootstrap-icons.css"), None), link(url("bootstrap.min.css"), None)],
+                scripts: vec![link(url("bootstrap.bundle.min.js"), Some(BOOTSTRAP_JS_INTEGRITY))],
+            },
+            Assets::Bundled => Links {
+                styles: vec![local("bootstrap-icons.css"), local("bootstrap.min.css")],
+                scripts: vec![local("bootstrap.bundle.min.js")],
+            },
+            Assets::Minimal => Links {
+                styles: vec![local("minimal.css")],
+                scripts: vec![local("minimal.js")],
+            },
+        }
+    }
+
+    // Bundled assets that are already there are kept, so later builds don't need to download them again,
+    // unless they don't match their integrity hash (e.g. a download cut short)
+    pub async fn write(&self, root: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let dir = format!("{root}/docs/assets");
+
+        match self {
+            Assets::Cdn => {},
+            Assets::Bundled => {
+                std::fs::create_dir_all(format!("{dir}/fonts"))?;
+
+                for (file, _, _) in DOWNLOADS {
+                    let path = format!("{dir}/{file}");
+                    let integrity = integrity(file);
+                    if std::fs::read(&path).is_ok_and(|bytes| integrity.is_none_or(|i| matches_integrity(&bytes, i))) {
+                        continue;
+                    }
+
+                    let url = url(file);
+                    let response = reqwest::get(&url)
+                        .await
+                        .and_then(|r| r.error_for_status())
+                        .map_err(|e| format!("Couldn't download {url} (use --assets minimal to build without it): {e}"))?;
+                    let bytes = response.bytes().await?;
+                    if integrity.is_some_and(|i| !matches_integrity(&bytes, i)) {
+                        return Err(format!("{url} doesn't match its integrity hash, so it wasn't saved").into());
+                    }
+                    if file == "bootstrap-icons.css" {
+                        std::fs::write(&path, strip_font_queries(&String::from_utf8_lossy(&bytes)))?;
+                    }
+                    else {
+                        std::fs::write(&path, bytes)?;
+                    }
+                }
+            },
+            Assets::Minimal => {
+                std::fs::create_dir_all(&dir)?;
+                for (file, contents) in MINIMAL {
+                    std::fs::write(format!("{dir}/{file}"), contents)?;
+                }
+            },
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn integrity_is_checked_against_the_sha384() {
+        let empty = "sha384-OLBgp1GsljhM2TJ+sbHjaiH9txEUvgdDTAzHv2P24donTt6/529l+9Ua0vFImLlb";
+        assert!(matches_integrity(b"", empty));
+        assert!(!matches_integrity(b"x", empty));
+        assert!(!matches_integrity(b"", &empty.replace("sha384-", "sha256-")));
+    }
+}
@@ -0,0 +1,31 @@
+// The parts of Bootstrap's JS the site uses: switching tabs and opening the page selector dropdown
+document.addEventListener("click", (event) => {
+    const tab = event.target.closest('[data-bs-toggle="tab"]');
+    if (tab) {
+        const list = tab.closest('[role="tablist"], .nav');
+        list.querySelectorAll('[data-bs-toggle="tab"]').forEach((other) => {
+            const selected = other === tab;
+            other.classList.toggle("active", selected);
+            other.setAttribute("aria-selected", selected ? "true" : "false");
+            const pane = document.querySelector(other.dataset.bsTarget);
+            if (pane) {
+                pane.classList.toggle("active", selected);
+                pane.classList.toggle("show", selected);
+            }
+        });
+        tab.dispatchEvent(new CustomEvent("shown.bs.tab", { bubbles: true }));
+        return;
+    }
+
+    const toggle = event.target.closest('[data-bs-toggle="dropdown"]');
+    document.querySelectorAll(".dropdown-menu.show").forEach((menu) => {
+        if (!toggle || menu !== toggle.nextElementSibling) {
+            menu.classList.remove("show");
+        }
+    });
+    if (toggle) {
+        const menu = toggle.nextElementSibling;
+        const open = menu.classList.toggle("show");
+        toggle.setAttribute("aria-expanded", open ? "true" : "false");
+    }
+});
@@ -0,0 +1,62 @@
+use rustc_hash::{FxHashMap, FxHashSet};
+use crate::{Cuber, Date};
+use crate::comp_stats::{StatsRow, StatsTable};
+
+const NTH_COMPS: [usize; 3] = [2, 3, 5];
+const YEARS_LATER: [usize; 4] = [1, 2, 3, 5];
+
+// For each year's newcomers, how many kept coming back
+pub fn retention_table(cubers: &[Cuber], comp_dates: &FxHashMap<String, Date>) -> StatsTable {
+    // Cohort year -> each member's (number of comps, years they competed in)
+    let mut cohorts = FxHashMap::<usize, Vec<(usize, FxHashSet<usize>)>>::default();
+    for c in cubers.iter() {
+        let dates = c.competition_dates(comp_dates);
+        if let Some(first) = dates.first() {
+            cohorts
+                .entry(first.year)
+                .or_default()
+                .push((dates.len(), dates.iter().map(|d| d.year).collect()));
+        }
+    }
+
+    // Can't say whether someone is still active in a year that hasn't finished yet
+    let latest_year = cohorts
+        .values()
+        .flat_map(|members| members.iter().flat_map(|(_, active)| active.iter().copied()))
+        .max()
+        .unwrap_or(0);
+
+    let mut years = cohorts.keys().copied().collect::<Vec<_>>();
+    years.sort_by_key(|y| std::cmp::Reverse(*y));
+    let largest = cohorts.values().map(|c| c.len()).max().unwrap_or(1) as f64;
+
+    let percent = |count: usize, total: usize| format!("{:.0}%", 100.0 * count as f64 / total as f64);
+
+    StatsTable {
+        label: "Retention".to_string(),
+        headers: vec![
+            "Cohort", "Newcomers", 
+            "2nd Comp", "3rd Comp", "5th Comp", 
+            "Active 1 Year Later", "2 Years Later", "3 Years Later", "5 Years Later"
+        ],
+        rows: years.into_iter().map(|year| {
+            let members = &cohorts[&year];
+            let mut cells = vec![year.to_string(), members.len().to_string()];
+
+            cells.extend(NTH_COMPS.iter().map(|n| {
+                percent(members.iter().filter(|(comps, _)| comps >= n).count(), members.len())
+            }));
+
+            cells.extend(YEARS_LATER.iter().map(|n| {
+                if year + n < latest_year {
+                    percent(members.iter().filter(|(_, active)| active.contains(&(year + n))).count(), members.len())
+                }
+                else {
+                    "".to_string()
+                }
+            }));
+
+            StatsRow { cells, bar: Some(members.len() as f64 / largest) }
+        }).collect(),
+    }
+}
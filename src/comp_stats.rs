@@ -0,0 +1,207 @@
+use rustc_hash::{FxHashMap, FxHashSet};
+use crate::{Date, escape, Labelled, PageItem, Ranked, ToCsv, ToHtml, ToJson, WCACompetition, WCAResult};
+
+const MONTHS: [&str; 12] = ["January", "February", "March", "April", "May", "June", "July", "August", "September", "October", "November", "December"];
+
+// A plain table of pre-formatted cells, with an optional bar drawn in the last column
+pub struct StatsTable {
+    pub label: String,
+    pub headers: Vec<&'static str>,
+    pub rows: Vec<StatsRow>,
+}
+
+pub struct StatsRow {
+    pub cells: Vec<String>,
+    pub bar: Option<f64>, // Fraction of the widest bar
+}
+
+impl Labelled for StatsTable {
+    fn get_label(&self) -> String {
+        self.label.to_string()
+    }
+}
+
+// Nobody is ranked in these, so there's no movement to track
+impl Ranked for StatsTable {}
+
+impl PageItem for StatsTable {}
+
+// The cells are already formatted, so there are no raw values to give
+impl ToJson for StatsTable {
+    fn to_json(&self) -> serde_json::Value {
+        let rows = self.rows.iter().map(|r| &r.cells).collect::<Vec<_>>();
+        serde_json::json!({ "label": self.label, "headers": self.headers, "rows": rows })
+    }
+}
+
+impl ToCsv for StatsTable {
+    fn to_csv(&self) -> Option<String> {
+        let mut writer = csv::Writer::from_writer(Vec::new());
+        writer.write_record(&self.headers).ok()?;
+        for row in self.rows.iter() {
+            writer.write_record(&row.cells).ok()?;
+        }
+        String::from_utf8(writer.into_inner().ok()?).ok()
+    }
+}
+
+impl ToHtml for StatsTable {
+    fn to_html_string(&self) -> String {
+        let has_bars = self.rows.iter().any(|r| r.bar.is_some());
+        let headers = self.headers.iter().map(|h| format!("<th> {} </th>", escape::text(h))).collect::<Vec<_>>().join("");
+        let chart_header = if has_bars { "<th class=\"w-50\"></th>" } else { "" };
+
+        let rows = self.rows.iter().map(|row| {
+            let cells = row.cells.iter().map(|c| format!("<td> {} </td>", escape::text(c))).collect::<Vec<_>>().join("");
+            let bar = match row.bar {
+                Some(fraction) => format!(
+                    r#"<td><div class="bg-info rounded" style="width: {:.1}%; height: 1em;"></div></td>"#, 
+                    100.0 * fraction
+                ),
+                None if has_bars => "<td></td>".to_string(),
+                None => "".to_string(),
+            };
+            format!("<tr> {cells} {bar} </tr>")
+        }).collect::<Vec<_>>().join("\n");
+
+        format!(r#"
+            <table class="table table-striped" >
+                <tr>
+                    {headers}
+                    {chart_header}
+                </tr>
+                {rows}
+            </table>
+        "#)
+    }
+}
+
+struct CompSummary<'a> {
+    comp: &'a WCACompetition,
+    date: Date,
+    competitors: usize,
+    newcomers: usize,
+    events: usize,
+}
+
+// Tables about the WA competition scene as a whole.
+// results_by_person needs everyone's results (not just from WA comps) to tell who was a newcomer.
+pub fn competition_tables(wa_comps: &[WCACompetition], results_by_person: &FxHashMap<String, Vec<WCAResult>>, comp_dates: &FxHashMap<String, Date>) -> Vec<StatsTable> {
+    let mut competitors = FxHashMap::<&str, FxHashSet<&str>>::default();
+    let mut events = FxHashMap::<&str, FxHashSet<&str>>::default();
+    let mut newcomers = FxHashMap::<&str, usize>::default();
+
+    for (person, results) in results_by_person.iter() {
+        for r in results.iter() {
+            competitors.entry(&r.competitionId).or_default().insert(person);
+            events.entry(&r.competitionId).or_default().insert(&r.eventId);
+        }
+
+        let first_comp = results
+            .iter()
+            .filter_map(|r| comp_dates.get(&r.competitionId).map(|d| (*d, r.competitionId.as_str())))
+            .min();
+        if let Some((_, comp)) = first_comp {
+            *newcomers.entry(comp).or_default() += 1;
+        }
+    }
+
+    // Only comps that have actually happened (and have results in the export)
+    let mut comps = wa_comps
+        .iter()
+        .filter(|c| competitors.contains_key(c.id.as_str()))
+        .filter_map(|c| Some(CompSummary {
+            comp: c,
+            date: c.start_date()?,
+            competitors: competitors[c.id.as_str()].len(),
+            newcomers: newcomers.get(c.id.as_str()).copied().unwrap_or(0),
+            events: events[c.id.as_str()].len(),
+        }))
+        .collect::<Vec<_>>();
+    comps.sort_by_key(|c| std::cmp::Reverse(c.date));
+
+    vec![
+        yearly_table(&comps, &competitors),
+        per_comp_table(&comps),
+        seasonality_table(&comps),
+    ]
+}
+
+fn yearly_table(comps: &[CompSummary], competitors: &FxHashMap<&str, FxHashSet<&str>>) -> StatsTable {
+    let mut years = comps.iter().map(|c| c.date.year).collect::<Vec<_>>();
+    years.dedup(); // Already sorted newest first
+
+    let per_year = years.iter().map(|year| {
+        let held = comps.iter().filter(|c| c.date.year == *year).collect::<Vec<_>>();
+        let unique = held
+            .iter()
+            .flat_map(|c| competitors[c.comp.id.as_str()].iter())
+            .collect::<FxHashSet<_>>()
+            .len();
+        let total = held.iter().map(|c| c.competitors).sum::<usize>();
+        let new = held.iter().map(|c| c.newcomers).sum::<usize>();
+        (*year, held.len(), unique, total as f64 / held.len() as f64, new)
+    }).collect::<Vec<_>>();
+
+    let most = per_year.iter().map(|y| y.1).max().unwrap_or(1) as f64;
+
+    StatsTable {
+        label: "Per Year".to_string(),
+        headers: vec!["Year", "Competitions", "Unique Competitors", "Competitors Per Comp", "Newcomers"],
+        rows: per_year.into_iter().map(|(year, held, unique, average, new)| StatsRow {
+            cells: vec![year.to_string(), held.to_string(), unique.to_string(), average.to_html_string(), new.to_string()],
+            bar: Some(held as f64 / most),
+        }).collect(),
+    }
+}
+
+fn per_comp_table(comps: &[CompSummary]) -> StatsTable {
+    let most = comps.iter().map(|c| c.competitors).max().unwrap_or(1) as f64;
+
+    StatsTable {
+        label: "Per Competition".to_string(),
+        headers: vec!["Date", "Competition", "Competitors", "Newcomers", "Events"],
+        rows: comps.iter().map(|c| StatsRow {
+            cells: vec![c.date.to_string(), c.comp.name.clone(), c.competitors.to_string(), c.newcomers.to_string(), c.events.to_string()],
+            bar: Some(c.competitors as f64 / most),
+        }).collect(),
+    }
+}
+
+fn seasonality_table(comps: &[CompSummary]) -> StatsTable {
+    let mut per_month = [0; 12];
+    for c in comps.iter() {
+        per_month[c.date.month - 1] += 1;
+    }
+    let most = per_month.iter().copied().max().unwrap_or(0).max(1) as f64;
+
+    StatsTable {
+        label: "Seasonality".to_string(),
+        headers: vec!["Month", "Competitions"],
+        rows: MONTHS.iter().zip(per_month).map(|(month, count)| StatsRow {
+            cells: vec![month.to_string(), count.to_string()],
+            bar: Some(count as f64 / most),
+        }).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::escape::HOSTILE;
+
+    #[test]
+    fn stats_table_escapes_cells() {
+        let table = StatsTable {
+            label: "Per Competition".to_string(),
+            headers: vec!["Competition"],
+            rows: HOSTILE.iter().map(|n| StatsRow { cells: vec![n.to_string()], bar: None }).collect(),
+        };
+        let html = table.to_html_string();
+
+        for name in HOSTILE {
+            assert!(html.contains(&escape::text(name)));
+        }
+        assert!(!html.contains("<script") && !html.contains("<b>"));
+    }
+}
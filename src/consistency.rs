@@ -0,0 +1,153 @@
+use crate::{Cuber, Event, JsonCell, RankRow, RankTable, ResultValue, ToHtml, rank};
+use crate::movement::Movement;
+
+// Stats about a person's individual attempts in one event
+#[derive(Clone, Copy, PartialEq, PartialOrd)]
+pub enum Stat {
+    Blank,
+    Time(f64),  // Centiseconds
+    Moves(f64),
+    Percent(f64),
+    Count(usize),
+}
+
+impl ToHtml for Stat {
+    fn to_html_string(&self) -> String {
+        match self {
+            Stat::Blank => "".to_string(),
+            Stat::Time(t) => ResultValue::Time(t.round() as isize).to_string(),
+            Stat::Moves(m) => format!("{m:.2}"),
+            Stat::Percent(p) => format!("{p:.1}%"),
+            Stat::Count(c) => c.to_string(),
+        }
+    }
+}
+
+impl JsonCell for Stat {
+    fn raw(&self) -> serde_json::Value {
+        match self {
+            Stat::Blank => serde_json::Value::Null,
+            Stat::Time(v) | Stat::Moves(v) | Stat::Percent(v) => serde_json::json!(v),
+            Stat::Count(c) => serde_json::json!(c),
+        }
+    }
+
+    fn raw_csv(&self) -> Option<String> {
+        match self {
+            Stat::Time(t) => Some(format!("{t:.2}")),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Default)]
+struct AttemptStats {
+    attempts: usize,
+    solves: usize,
+    dnfs: usize,
+    dns: usize,
+    mean: Option<f64>,
+    std_dev: Option<f64>,
+}
+
+impl AttemptStats {
+    fn new(cuber: &Cuber, event: Event) -> Self {
+        let attempts = cuber.results
+            .iter()
+            .filter(|r| Event::str_to_index(&r.eventId) == event as usize)
+            .flat_map(|r| r.attempts())
+            .collect::<Vec<_>>();
+
+        let mut stats = AttemptStats { attempts: attempts.len(), ..Default::default() };
+        let mut values = Vec::new();
+
+        for attempt in attempts {
+            match attempt {
+                ResultValue::DNF => stats.dnfs += 1,
+                ResultValue::DNS => stats.dns += 1,
+                ResultValue::Multi { solved, attempted, .. } if 2 * solved < attempted => stats.dnfs += 1,
+                ResultValue::Multi { .. } => stats.solves += 1,
+                ResultValue::Time(v) | ResultValue::Moves(v) => {
+                    stats.solves += 1;
+                    values.push(v as f64);
+                },
+                ResultValue::None => {},
+            }
+        }
+
+        // Multi attempts aren't a single number, so no mean/deviation for those
+        if !values.is_empty() {
+            let mean = values.iter().sum::<f64>() / values.len() as f64;
+            let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / values.len() as f64;
+            stats.mean = Some(mean);
+            stats.std_dev = Some(variance.sqrt());
+        }
+
+        stats
+    }
+
+    fn percent(&self, count: usize, total: usize) -> Stat {
+        if total == 0 { Stat::Blank } else { Stat::Percent(100.0 * count as f64 / total as f64) }
+    }
+
+    // DNSs weren't really attempts, so they don't count against the success or DNF rate
+    fn success_rate(&self) -> Stat {
+        self.percent(self.solves, self.attempts - self.dns)
+    }
+
+    fn dnf_rate(&self) -> Stat {
+        self.percent(self.dnfs, self.attempts - self.dns)
+    }
+
+    fn value(v: Option<f64>, event: Event) -> Stat {
+        match v {
+            Some(v) if event == Event::_3fm => Stat::Moves(v),
+            Some(v) => Stat::Time(v),
+            None => Stat::Blank,
+        }
+    }
+
+    fn std_dev(&self, event: Event) -> Stat {
+        Self::value(self.std_dev, event)
+    }
+
+    fn data(&self, event: Event) -> [Stat; 6] {
+        [
+            Self::value(self.mean, event),
+            self.std_dev(event),
+            self.dnf_rate(),
+            self.percent(self.dns, self.attempts),
+            Stat::Count(self.solves),
+            Stat::Count(self.attempts),
+        ]
+    }
+}
+
+const HEADERS: [&str; 6] = ["Mean", "Std. Dev.", "DNF Rate", "DNS Rate", "Solves", "Attempts"];
+
+// Consistency and success rate leaderboards (for people with at least min_attempts), plus solve counts for everyone
+pub fn consistency_tables(cubers: &[Cuber], event: Event, min_attempts: usize) -> Vec<RankTable<'_, Stat, Stat, 6>> {
+    let stats = cubers
+        .iter()
+        .map(|c| (c, AttemptStats::new(c, event)))
+        .filter(|(_, s)| s.attempts > 0)
+        .collect::<Vec<_>>();
+
+    let table = |label: &str, score: &dyn Fn(&AttemptStats) -> Stat, minimum: usize, ascending: bool| {
+        let mut rows = stats
+            .iter()
+            .filter(|(_, s)| s.attempts >= minimum && score(s) != Stat::Blank)
+            .map(|(c, s)| RankRow { rank: 0, score: score(s), data: s.data(event), person: c, movement: Movement::Unknown })
+            .collect::<Vec<_>>();
+        rank(&mut rows, ascending);
+        RankTable { label: label.to_string(), rows, headers: HEADERS }
+    };
+
+    let mut tables = Vec::new();
+    if event != Event::_3mbld {
+        tables.push(table("Consistency", &|s| s.std_dev(event), min_attempts, true));
+    }
+    tables.push(table("Success Rate", &|s| s.success_rate(), min_attempts, false));
+    tables.push(table("Solves", &|s| Stat::Count(s.solves), 0, false));
+    tables
+}
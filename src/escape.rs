@@ -0,0 +1,43 @@
+// Escaping for data (people's names, competition names, labels) on its way into HTML.
+// Templates escape their variables themselves, so this is for the HTML that's still built in Rust.
+
+// For text between tags
+pub fn text(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+// For attribute values, quoted with either " or '
+pub fn attr(s: &str) -> String {
+    text(s)
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+// Names and labels that break a page if they aren't escaped, for each renderer's tests
+#[cfg(test)]
+pub const HOSTILE: [&str; 4] = [
+    "<script>alert(1)</script>",
+    "Bob <b>Jones</b>",
+    "Carol & \"Dee\"",
+    "O'Neil' onmouseover='alert(1)",
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_escapes_markup() {
+        assert_eq!(text("<b>Bob</b> & co"), "&lt;b&gt;Bob&lt;/b&gt; &amp; co");
+        assert_eq!(text("already &amp;"), "already &amp;amp;");
+        assert_eq!(text("Plain Name"), "Plain Name");
+    }
+
+    #[test]
+    fn attr_escapes_quotes() {
+        assert_eq!(attr(r#"a "b" 'c'"#), "a &quot;b&quot; &#39;c&#39;");
+        assert_eq!(attr("<&>"), "&lt;&amp;&gt;");
+    }
+}
@@ -0,0 +1,209 @@
+use std::collections::BTreeMap;
+use rustc_hash::FxHashMap;
+use serde::Serialize;
+use crate::{Cuber, Date, Event, Labelled, PageItem, Ranked, ToCsv, ToHtml, ToJson};
+
+// Head to head records are written as data files under docs/h2h/ and looked up by the page in the browser:
+//   people.js   - [id, name] for every resident
+//   rounds.js   - [competition id, competition name, date, event id, round type] for every round with 2+ residents
+//   <id>.js     - that person's rounds and their win/loss/tie record against everyone they've met
+// The list of encounters between two people is the overlap of their rounds, so it isn't stored twice.
+// Each file is a script that adds its JSON to h2hData, since browsers block fetch for pages opened from disk
+// but still run scripts, so the page works offline too.
+
+#[derive(Serialize)]
+struct Round(String, String, String, String, String);
+
+// (index into rounds.json, placing, single, average)
+#[derive(Serialize)]
+struct PersonRound(usize, usize, String, String);
+
+#[derive(Serialize)]
+struct PersonFile {
+    rounds: Vec<PersonRound>,
+    // Opponent id -> event id -> [wins, losses, ties]
+    records: BTreeMap<String, BTreeMap<String, [usize; 3]>>,
+}
+
+pub fn write_data(cubers: &[Cuber], comp_names: &FxHashMap<String, String>, comp_dates: &FxHashMap<String, Date>, dir: &str) -> std::io::Result<()> {
+    std::fs::create_dir_all(dir)?;
+
+    // (comp, event, round) -> (index into cubers, placing, single, average) for every resident in it.
+    // Only the tracked events count, and they're named like the rest of the site (so old multi is just multi).
+    let mut rounds = FxHashMap::<(&str, &str, &str), Vec<(usize, usize, String, String)>>::default();
+    for (i, cuber) in cubers.iter().enumerate() {
+        for r in cuber.results.iter().filter(|r| Event::from_id(&r.eventId).is_some()) {
+            if let Ok(pos) = r.pos.parse::<usize>() {
+                rounds
+                    .entry((&r.competitionId, &r.eventId, &r.roundTypeId))
+                    .or_default()
+                    .push((i, pos, r.get_single().to_string(), r.get_average().to_string()));
+            }
+        }
+    }
+
+    let mut shared = rounds
+        .into_iter()
+        .filter(|(_, people)| people.len() > 1)
+        .collect::<Vec<_>>();
+    shared.sort_by_key(|((comp, event, round), _)| (comp_dates.get(*comp).copied(), *comp, Event::str_to_index(event), *round));
+
+    let mut files = cubers
+        .iter()
+        .map(|_| PersonFile { rounds: Vec::new(), records: BTreeMap::new() })
+        .collect::<Vec<_>>();
+    let mut round_list = Vec::new();
+
+    for ((comp, event_id, round), people) in shared.into_iter() {
+        let Some(event) = Event::from_id(event_id) else { continue };
+        let idx = round_list.len();
+        round_list.push(Round(
+            comp.to_string(),
+            comp_names.get(comp).cloned().unwrap_or(comp.to_string()),
+            comp_dates.get(comp).map(|d| d.to_string()).unwrap_or_default(),
+            event.to_string(),
+            round.to_string(),
+        ));
+
+        for (a, pos_a, single, average) in people.iter() {
+            files[*a].rounds.push(PersonRound(idx, *pos_a, single.clone(), average.clone()));
+
+            for (b, pos_b, _, _) in people.iter().filter(|(b, ..)| b != a) {
+                let record = files[*a].records
+                    .entry(cubers[*b].id.clone())
+                    .or_default()
+                    .entry(event.to_string())
+                    .or_default();
+                let outcome = match pos_a.cmp(pos_b) {
+                    std::cmp::Ordering::Less => 0,
+                    std::cmp::Ordering::Greater => 1,
+                    std::cmp::Ordering::Equal => 2,
+                };
+                record[outcome] += 1;
+            }
+        }
+    }
+
+    let mut people = cubers.iter().map(|c| [c.id.as_str(), c.name.as_str()]).collect::<Vec<_>>();
+    people.sort_by_key(|[_, name]| *name);
+
+    write_script(dir, "people", &people)?;
+    write_script(dir, "rounds", &round_list)?;
+    for (cuber, file) in cubers.iter().zip(files) {
+        write_script(dir, &cuber.id, &file)?;
+    }
+
+    Ok(())
+}
+
+fn write_script<T: Serialize>(dir: &str, name: &str, data: &T) -> std::io::Result<()> {
+    std::fs::write(
+        format!("{dir}/{name}.js"),
+        format!("h2hData[{}] = {};\n", serde_json::to_string(name)?, serde_json::to_string(data)?)
+    )
+}
+
+// The head to head page with person A already picked, e.g. from their profile
+pub fn page_link(id: &str) -> String {
+    format!("h2h.html#{id}")
+}
+
+// Picker for two people, filled in from the data files. Person A can be picked with the URL's hash (see page_link).
+pub struct HeadToHead;
+
+impl Labelled for HeadToHead {
+    fn get_label(&self) -> String {
+        "Compare".to_string()
+    }
+}
+
+impl Ranked for HeadToHead {}
+
+impl PageItem for HeadToHead {}
+
+impl ToCsv for HeadToHead {}
+
+// The records themselves are already JSON, so just point at them
+impl ToJson for HeadToHead {
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({ "label": self.get_label(), "people": "h2h/people.js", "rounds": "h2h/rounds.js", "records": "h2h/<id>.js" })
+    }
+}
+
+impl ToHtml for HeadToHead {
+    fn to_html_string(&self) -> String {
+        let event_names = serde_json::to_string(
+            &BTreeMap::from_iter(Event::iter().map(|e| (e.to_string(), e.to_nice_str())))
+        ).unwrap_or_default();
+
+        format!(r#"
+            <div class="row g-3 my-3">
+                <div class="col-md"><select class="form-select" id="h2h-a"></select></div>
+                <div class="col-md-auto align-self-center">vs</div>
+                <div class="col-md"><select class="form-select" id="h2h-b"></select></div>
+            </div>
+            <div id="h2h-summary"></div>
+            <div id="h2h-encounters"></div>
+            <script>
+                const eventNames = {event_names};
+                window.h2hData = {{}};
+                const load = (file) => file in h2hData ? Promise.resolve(h2hData[file]) : new Promise((resolve, reject) => {{
+                    const script = document.createElement("script");
+                    script.src = "h2h/" + file + ".js";
+                    script.onload = () => resolve(h2hData[file]);
+                    script.onerror = reject;
+                    document.head.append(script);
+                }});
+                const text = (s) => {{ const d = document.createElement("div"); d.textContent = s; return d.innerHTML; }};
+                const selects = [document.getElementById("h2h-a"), document.getElementById("h2h-b")];
+
+                async function compare() {{
+                    const [a, b] = selects.map(s => s.value);
+                    if (!a || !b || a === b) {{ return; }}
+                    const [rounds, personA, personB] = await Promise.all([load("rounds"), load(a), load(b)]);
+                    const record = personA.records[b] || {{}};
+
+                    const summary = Object.entries(record).map(([event, [w, l, t]]) =>
+                        `<tr><td>${{text(eventNames[event] || event)}}</td><td>${{w}}</td><td>${{l}}</td><td>${{t}}</td></tr>`
+                    ).join("");
+                    document.getElementById("h2h-summary").innerHTML = summary 
+                        ? `<table class="table table-striped"><tr><th>Event</th><th>Wins</th><th>Losses</th><th>Ties</th></tr>${{summary}}</table>`
+                        : "<p>These two have never competed in the same round.</p>";
+
+                    const theirs = new Map(personB.rounds.map(r => [r[0], r]));
+                    const encounters = personA.rounds.filter(r => theirs.has(r[0])).reverse().map(r => {{
+                        const o = theirs.get(r[0]);
+                        const [comp, compName, date, event, round] = rounds[r[0]];
+                        return `<tr><td>${{date}}</td><td>${{text(compName)}}</td><td>${{text(eventNames[event] || event)}}</td><td>${{text(round)}}</td>`
+                            + `<td>${{r[1]}}</td><td>${{text(r[2])}}</td><td>${{text(r[3])}}</td>`
+                            + `<td>${{o[1]}}</td><td>${{text(o[2])}}</td><td>${{text(o[3])}}</td></tr>`;
+                    }}).join("");
+                    document.getElementById("h2h-encounters").innerHTML = encounters 
+                        ? `<table class="table table-striped"><tr><th>Date</th><th>Competition</th><th>Event</th><th>Round</th>`
+                            + `<th>Place</th><th>Single</th><th>Average</th><th>Their Place</th><th>Their Single</th><th>Their Average</th></tr>${{encounters}}</table>`
+                        : "";
+                }}
+
+                // Picks person A from the hash, with person B as whoever's first apart from them
+                const pickFromHash = (people) => {{
+                    const id = decodeURIComponent(location.hash.slice(1));
+                    if (people.some(([p]) => p === id)) {{ selects[0].value = id; }}
+                    if (people.length > 1) {{ selects[1].selectedIndex = selects[0].selectedIndex === 1 ? 0 : 1; }}
+                }};
+
+                load("people").then(people => {{
+                    for (const select of selects) {{
+                        for (const [id, name] of people) {{
+                            select.add(new Option(name + " (" + id + ")", id));
+                        }}
+                        select.addEventListener("change", compare);
+                    }}
+                    selects[0].addEventListener("change", () => history.replaceState(null, '', '#' + selects[0].value));
+                    window.addEventListener("hashchange", () => {{ pickFromHash(people); compare(); }});
+                    pickFromHash(people);
+                    compare();
+                }});
+            </script>
+        "#)
+    }
+}
@@ -0,0 +1,143 @@
+use crate::{escape, Event, Labelled, PageItem, RankRow, Ranked, ResultValue, ToCsv, ToHtml, ToJson};
+use crate::progression::{axis_label, plot_value};
+use crate::svg::Frame;
+
+const FRAME: Frame = Frame { height: 240.0, left: 40.0, top: 30.0 }; // The top is room for the percentile labels
+const MAX_BINS: usize = 20;
+
+// Lines drawn over the bars at a percentile of the ranking, e.g. 10 for the top 10%
+const MARKERS: [(&str, usize); 2] = [("Top 10%", 10), ("Median", 50)];
+
+struct Bin {
+    from: f64,
+    to: f64,
+    ranks: Option<(usize, usize)>, // First and last rank in the bin
+    count: usize,
+}
+
+// How a ranking's results are spread out, drawn as a histogram
+pub struct Histogram {
+    pub label: String,
+    pub event: Event,
+    bins: Vec<Bin>,
+    markers: Vec<(&'static str, usize, ResultValue)>, // (label, rank, result)
+    values: Vec<f64>, // For placing the markers
+}
+
+impl Histogram {
+    // rows must already be ranked, as they are for the tables
+    pub fn new<D, const N: usize>(label: &str, event: Event, rows: &[RankRow<ResultValue, D, N>], average: bool) -> Self {
+        let ranked = rows
+            .iter()
+            .filter_map(|r| plot_value(r.score, event, average).map(|v| (r.rank, r.score, v)))
+            .collect::<Vec<_>>();
+        let values = ranked.iter().map(|(_, _, v)| *v).collect::<Vec<_>>();
+
+        let markers = MARKERS
+            .iter()
+            .filter_map(|(label, percent)| {
+                let index = (ranked.len() * percent).div_ceil(100).checked_sub(1)?;
+                ranked.get(index).map(|(rank, score, _)| (*label, *rank, *score))
+            })
+            .collect();
+
+        // The slowest few would squash everyone else into a couple of bars, so they share the last one.
+        // Multi is plotted by points, where lower is worse, so for it that's the first bar.
+        let (Some(best), Some(limit)) = (values.first(), values.get(values.len().saturating_sub(1) * 95 / 100)) else {
+            return Histogram { label: label.to_string(), event, bins: Vec::new(), markers, values };
+        };
+        let (low, high) = (best.min(*limit), best.max(*limit));
+        let count = ((values.len() as f64).sqrt().ceil() as usize).clamp(1, MAX_BINS);
+        let width = ((high - low) / count as f64).max(1.0);
+
+        let mut bins = (0..count)
+            .map(|i| Bin { from: low + width * i as f64, to: low + width * (i + 1) as f64, ranks: None, count: 0 })
+            .collect::<Vec<_>>();
+        for (rank, _, value) in ranked.iter() {
+            let bin = &mut bins[(((value - low) / width).floor().max(0.0) as usize).min(count - 1)];
+            bin.count += 1;
+            bin.ranks = Some(bin.ranks.map_or((*rank, *rank), |(first, last)| (first.min(*rank), last.max(*rank))));
+        }
+
+        Histogram { label: label.to_string(), event, bins, markers, values }
+    }
+
+    fn svg(&self) -> String {
+        let (Some(first), Some(last)) = (self.bins.first(), self.bins.last()) else {
+            return String::new();
+        };
+        let (low, high) = (first.from, last.to);
+        let tallest = self.bins.iter().map(|b| b.count).max().unwrap_or(1).max(1) as f64;
+
+        let x = |value: f64| FRAME.x(value.clamp(low, high), low, high);
+        let y = |count: f64| FRAME.y(count, 0.0, tallest);
+
+        let mut svg = FRAME.open(&format!("{} {} distribution", self.event.to_nice_str(), self.label));
+        svg += &FRAME.y_labels([0.0, (tallest / 2.0).round(), tallest].map(|count| (y(count), count.to_string())));
+        svg += &FRAME.x_labels((0..=4).map(|i| {
+            let value = low + (high - low) * i as f64 / 4.0;
+            (x(value), axis_label(value, self.event))
+        }));
+
+        // Hovering over a bar shows which ranks are in it
+        for bin in self.bins.iter() {
+            let title = match bin.ranks {
+                Some((first, last)) if first == last => format!("Rank {first}"),
+                Some((first, last)) => format!("Ranks {first}-{last}"),
+                None => "Nobody".to_string(),
+            };
+            svg += &format!(
+                r#"<rect x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}" style="fill: var(--bs-info);" fill-opacity="0.7" stroke="var(--bs-body-bg)"><title>{title}: {} to {} ({} people)</title></rect>"#,
+                x(bin.from), y(bin.count as f64), x(bin.to) - x(bin.from), y(0.0) - y(bin.count as f64),
+                escape::text(&axis_label(bin.from, self.event)), escape::text(&axis_label(bin.to, self.event)), bin.count
+            );
+        }
+
+        for (i, (label, rank, result)) in self.markers.iter().enumerate() {
+            let Some(value) = self.values.get(rank - 1) else { continue };
+            let px = x(*value);
+            svg += &format!(
+                r#"<path d="M {px:.1} {} V {}" style="stroke: var(--bs-danger);" stroke-dasharray="4 3"/><text x="{px:.1}" y="{}" text-anchor="middle" style="fill: var(--bs-danger);">{label}: {} (rank {rank})</text>"#,
+                FRAME.top - 5.0, FRAME.bottom(), 10.0 + 12.0 * i as f64, escape::text(&result.to_string())
+            );
+        }
+
+        svg + "</svg>"
+    }
+}
+
+impl Labelled for Histogram {
+    fn get_label(&self) -> String {
+        self.label.to_string()
+    }
+}
+
+impl Ranked for Histogram {}
+
+impl ToCsv for Histogram {}
+
+impl PageItem for Histogram {}
+
+impl ToJson for Histogram {
+    fn to_json(&self) -> serde_json::Value {
+        let bins = self.bins.iter().map(|b| serde_json::json!({
+            "from": b.from,
+            "to": b.to,
+            "ranks": b.ranks.map(|(first, last)| [first, last]),
+            "count": b.count,
+        })).collect::<Vec<_>>();
+        let markers = self.markers.iter().map(|(label, rank, result)| serde_json::json!({
+            "label": label,
+            "rank": rank,
+            "result": result.to_string(),
+        })).collect::<Vec<_>>();
+
+        serde_json::json!({ "label": self.label, "bins": bins, "markers": markers })
+    }
+}
+
+impl ToHtml for Histogram {
+    fn to_html_string(&self) -> String {
+        format!(r#"<div class="mt-3">{}</div>"#, self.svg())
+    }
+}
@@ -5,6 +5,33 @@ use reqwest::dns::Resolving;
 use rustc_hash::{FxHashSet, FxHashMap};
 use macros::struct_from_tsv;
 
+mod options;
+mod movement;
+mod yearly;
+mod wca_ranks;
+mod comp_stats;
+mod cohorts;
+mod consistency;
+mod milestones;
+mod head_to_head;
+mod ratings;
+mod wcif;
+mod psych_sheet;
+mod predictions;
+mod provisional;
+mod templates;
+mod escape;
+mod progression;
+mod histogram;
+mod svg;
+mod assets;
+use options::Options;
+use assets::Assets;
+use wca_ranks::WCARanks;
+use provisional::{Provisional, ProvisionalComp};
+use yearly::{year_results, year_table, YearResults};
+use movement::{Movement, Ranked, RankHistory};
+
 pub trait Table {
     const PATH: & 'static str;
     fn Read<'a>() -> Result<DeserializeRecordsIntoIter<std::fs::File, Self>, csv::Error> 
@@ -21,6 +48,115 @@ impl Table for WCACompetition {
     const PATH: & 'static str = "./data/WCA_export_Competitions.tsv";
 }
 
+// A competition with just the id and dates filled in
+#[cfg(test)]
+impl WCACompetition {
+    pub fn test(year: &str, month: &str, day: &str, end_month: &str, end_day: &str) -> Self {
+        WCACompetition {
+            id: "Test2024".to_string(),
+            year: year.to_string(), month: month.to_string(), day: day.to_string(), endMonth: end_month.to_string(), endDay: end_day.to_string(),
+            ..Default::default()
+        }
+    }
+}
+
+impl WCACompetition {
+    pub fn start_date(&self) -> Option<Date> {
+        Date::from_parts(&self.year, &self.month, &self.day)
+    }
+
+    pub fn end_date(&self) -> Option<Date> {
+        let start = self.start_date()?;
+        let end = Date::from_parts(&self.year, &self.endMonth, &self.endDay)?;
+        if end < start { // Comp ran over new year
+            Some(Date { year: end.year + 1, ..end })
+        }
+        else {
+            Some(end)
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub struct Date {
+    pub year: usize,
+    pub month: usize,
+    pub day: usize,
+}
+
+impl Date {
+    pub fn from_parts(year: &str, month: &str, day: &str) -> Option<Self> {
+        let date = Date { 
+            year: year.trim().parse().ok()?, 
+            month: month.trim().parse().ok()?, 
+            day: day.trim().parse().ok()? 
+        };
+        // A day past the end of its month (e.g. Feb 30th) comes back as a day in the next month
+        if (1..=12).contains(&date.month) && (1..=31).contains(&date.day) && Self::from_days_since_epoch(date.days_since_epoch()) == date {
+            Some(date)
+        }
+        else {
+            None
+        }
+    }
+
+    pub fn today() -> Self {
+        let secs = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        Self::from_days_since_epoch((secs / 86400) as i64)
+    }
+
+    // Howard Hinnant's civil_from_days
+    fn from_days_since_epoch(days: i64) -> Self {
+        let z = days + 719468;
+        let era = z.div_euclid(146097);
+        let doe = z.rem_euclid(146097);
+        let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = doy - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+        Date { year: year as usize, month: month as usize, day: day as usize }
+    }
+
+    // Howard Hinnant's days_from_civil
+    fn days_since_epoch(&self) -> i64 {
+        let (month, day) = (self.month as i64, self.day as i64);
+        let year = self.year as i64 - if month <= 2 { 1 } else { 0 };
+        let era = year.div_euclid(400);
+        let yoe = year.rem_euclid(400);
+        let doy = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day - 1;
+        let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+        era * 146097 + doe - 719468
+    }
+
+    pub fn plus_days(&self, n: usize) -> Self {
+        Self::from_days_since_epoch(self.days_since_epoch() + n as i64)
+    }
+
+    // Same day of the month, n months earlier (only used for comparisons, so Feb 31st is fine)
+    pub fn months_before(&self, n: usize) -> Self {
+        let months = (self.year * 12 + self.month - 1).saturating_sub(n);
+        Date { year: months / 12, month: months % 12 + 1, day: self.day }
+    }
+
+    // Parses YYYY-MM-DD
+    pub fn parse(s: &str) -> Option<Self> {
+        let mut parts = s.split('-');
+        let date = Self::from_parts(parts.next()?, parts.next()?, parts.next()?)?;
+        if parts.next().is_none() { Some(date) } else { None }
+    }
+}
+
+impl std::fmt::Display for Date {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(f, "{:0>4}-{:0>2}-{:0>2}", self.year, self.month, self.day)
+    }
+}
+
 struct_from_tsv!(WCAPerson subid   name    countryId       gender  id);
 impl Table for WCAPerson {
     const PATH: & 'static str = "./data/WCA_export_Persons.tsv";
@@ -31,6 +167,29 @@ impl Table for WCAResult {
     const PATH: & 'static str = "./data/WCA_export_Results.tsv";
 }
 
+// A 3x3 final at the test competition (see WCACompetition::test), with a 10.00 single and 12.00 average
+#[cfg(test)]
+impl WCAResult {
+    pub fn test(person: &str, pos: usize) -> Self {
+        WCAResult {
+            competitionId: "Test2024".to_string(), eventId: "333".to_string(), roundTypeId: "f".to_string(), pos: pos.to_string(),
+            best: "1000".to_string(), average: "1200".to_string(), personName: person.to_string(), personId: person.to_string(),
+            formatId: "a".to_string(),
+            ..Default::default()
+        }
+    }
+}
+
+struct_from_tsv!(WCARankSingle personId eventId best worldRank continentRank countryRank);
+impl Table for WCARankSingle {
+    const PATH: & 'static str = "./data/WCA_export_RanksSingle.tsv";
+}
+
+struct_from_tsv!(WCARankAverage personId eventId best worldRank continentRank countryRank);
+impl Table for WCARankAverage {
+    const PATH: & 'static str = "./data/WCA_export_RanksAverage.tsv";
+}
+
 impl WCAResult {
     pub fn get_average(&self) -> ResultValue {
         let mut output: ResultValue = ResultValue::None;
@@ -46,6 +205,19 @@ impl WCAResult {
     pub fn get_single(&self) -> ResultValue {
         ResultValue::from_str(&self.eventId, &self.best)
     }
+
+    // Every attempt actually made in the round (skipping the empty ones in shorter formats/cutoffs)
+    pub fn attempts(&self) -> Vec<ResultValue> {
+        [&self.value1, &self.value2, &self.value3, &self.value4, &self.value5]
+            .iter()
+            .map(|v| match v.as_str() { // from_str doesn't catch these for multi
+                "-1" => ResultValue::DNF,
+                "-2" => ResultValue::DNS,
+                _    => ResultValue::from_str(&self.eventId, v),
+            })
+            .filter(|v| !matches!(v, ResultValue::None))
+            .collect()
+    }
 }
 
 #[derive(Clone, Copy)]
@@ -288,6 +460,10 @@ impl Event {
         }
     }
 
+    pub fn from_id(event_id: &str) -> Option<Self> {
+        Self::iter().nth(Self::str_to_index(event_id)).copied()
+    }
+
     pub fn iter() -> std::slice::Iter<'static, Self> {
         static Events: [Event; 18] = [ Event::_skewb, Event::_2, Event::_3, Event::_3bld, Event::_3oh, Event::_3mbld, Event::_3fm, Event::_3ft, Event::_4, Event::_4bld, Event::_5, Event::_5bld, Event::_6, Event::_7, Event::_sq1, Event::_pyram, Event::_minx, Event::_clock];
         Events.iter()
@@ -296,6 +472,28 @@ impl Event {
     pub fn to_nice_str(&self) -> &str {
         Self::EVENT_DISPLAY_STRINGS[*self as usize]
     }
+
+    // Sub-X barriers tracked on the milestones page. Achieving one means getting strictly under it.
+    // (FM singles are in moves, but FM means are in hundredths of a move like the export)
+    pub const MILESTONES: [Milestone; 10] = [
+        Milestone { label: "Sub-20 3x3 Average", event: Event::_3, average: true, under: ResultValue::Time(2000) },
+        Milestone { label: "Sub-15 3x3 Average", event: Event::_3, average: true, under: ResultValue::Time(1500) },
+        Milestone { label: "Sub-10 3x3 Average", event: Event::_3, average: true, under: ResultValue::Time(1000) },
+        Milestone { label: "Sub-10 3x3 Single", event: Event::_3, average: false, under: ResultValue::Time(1000) },
+        Milestone { label: "Sub-5 2x2 Average", event: Event::_2, average: true, under: ResultValue::Time(500) },
+        Milestone { label: "Sub-1:00 4x4 Average", event: Event::_4, average: true, under: ResultValue::Time(6000) },
+        Milestone { label: "Sub-2:00 5x5 Average", event: Event::_5, average: true, under: ResultValue::Time(12000) },
+        Milestone { label: "Sub-20 OH Average", event: Event::_3oh, average: true, under: ResultValue::Time(2000) },
+        Milestone { label: "Sub-30 3BLD Single", event: Event::_3bld, average: false, under: ResultValue::Time(3000) },
+        Milestone { label: "Sub-30 FM Mean", event: Event::_3fm, average: true, under: ResultValue::Moves(3000) },
+    ];
+}
+
+pub struct Milestone {
+    pub label: &'static str,
+    pub event: Event,
+    pub average: bool,
+    pub under: ResultValue,
 }
 
 impl ToString for Event {
@@ -309,6 +507,9 @@ pub struct Cuber {
     pub results : Vec<WCAResult>,
     pub id : String,
     pub name : String,
+    gender : String, // Only used for women's rankings, never shown
+    pub last_competed: Option<Date>, // For the "active in the last 2 years" filter
+    pub has_profile: bool, // Whether names link to a progression page
     singles : [ResultValue; 18],
     averages: [ResultValue; 18],
 }
@@ -322,6 +523,9 @@ impl Cuber {
             results,
             name,
             id,
+            gender: String::new(),
+            last_competed: None,
+            has_profile: false,
             singles: [ResultValue::None; 18],
             averages: [ResultValue::None; 18],
         };
@@ -340,6 +544,10 @@ impl Cuber {
         out
     }
 
+    pub fn is_female(&self) -> bool {
+        self.gender == "f"
+    }
+
     pub fn get_average_from_str(&self, eventId: &str) -> ResultValue {
         let idx = Event::str_to_index(&eventId);
         if idx != 18 {
@@ -367,12 +575,52 @@ impl Cuber {
     pub fn get_average(&self, event: Event) -> ResultValue {
         self.averages[event as usize]
     }
+
+    // Dates of every competition this person has results from, oldest first
+    pub fn competition_dates(&self, comp_dates: &FxHashMap<String, Date>) -> Vec<Date> {
+        let mut dates = self.results
+            .iter()
+            .map(|r| r.competitionId.as_str())
+            .collect::<FxHashSet<_>>()
+            .into_iter()
+            .filter_map(|id| comp_dates.get(id).copied())
+            .collect::<Vec<_>>();
+        dates.sort();
+        dates
+    }
+
+    pub fn first_competition(&self, comp_dates: &FxHashMap<String, Date>) -> Option<Date> {
+        self.competition_dates(comp_dates).first().copied()
+    }
+
+    // Same person, but only counting the results that pass the filter (None if nothing is left)
+    pub fn filtered<F>(&self, keep: F) -> Option<Self> where F: Fn(&WCAResult) -> bool {
+        let results = self.results.iter().filter(|r| keep(r)).cloned().collect::<Vec<_>>();
+        if results.is_empty() {
+            None
+        }
+        else {
+            Some(Cuber { gender: self.gender.clone(), last_competed: self.last_competed, has_profile: self.has_profile, ..Cuber::new(results) })
+        }
+    }
 }
 
 pub trait ToHtml {
     fn to_html_string(&self) -> String;
 }
 
+// What a tab shows. Only HTML rendered from a template can fail (e.g. an error in a --templates override), which should stop the build,
+// so template backed items (rank tables) implement this instead of ToHtml.
+pub trait TryToHtml {
+    fn try_to_html_string(&self) -> Result<String, String>;
+}
+
+impl<T: ToHtml> TryToHtml for T {
+    fn try_to_html_string(&self) -> Result<String, String> {
+        Ok(self.to_html_string())
+    }
+}
+
 impl ToHtml for f64 {
     fn to_html_string(&self) -> String {
         format!("{:.2}", self)
@@ -385,17 +633,114 @@ impl ToHtml for usize {
     }
 }
 
+impl ToHtml for Date {
+    fn to_html_string(&self) -> String {
+        self.to_string()
+    }
+}
+
 impl ToHtml for ResultValue {
     fn to_html_string(&self) -> String {
         self.to_string()
     }
 }
 
+impl<T: ToHtml> ToHtml for Option<T> {
+    fn to_html_string(&self) -> String {
+        self.as_ref().map(|v| v.to_html_string()).unwrap_or_default()
+    }
+}
+
+// For the JSON copy of each page, so other tools don't have to scrape the HTML
+pub trait ToJson {
+    fn to_json(&self) -> serde_json::Value;
+}
+
+// For the spreadsheet copy of each table. Items that aren't a table (e.g. head to head) have none.
+pub trait ToCsv {
+    fn to_csv(&self) -> Option<String> {
+        None
+    }
+}
+
+// A value shown in a table cell, as both its raw value and the text the site shows
+pub trait JsonCell: ToHtml {
+    fn raw(&self) -> serde_json::Value;
+
+    fn formatted(&self) -> String {
+        self.to_html_string()
+    }
+
+    fn cell_json(&self) -> serde_json::Value {
+        serde_json::json!({ "raw": self.raw(), "formatted": self.formatted() })
+    }
+
+    // Only results get a raw column in the CSVs, since their shown form can't be sorted or summed
+    fn raw_csv(&self) -> Option<String> {
+        None
+    }
+}
+
+impl JsonCell for f64 {
+    fn raw(&self) -> serde_json::Value {
+        serde_json::json!(self)
+    }
+}
+
+impl JsonCell for usize {
+    fn raw(&self) -> serde_json::Value {
+        serde_json::json!(self)
+    }
+}
+
+impl JsonCell for Date {
+    fn raw(&self) -> serde_json::Value {
+        serde_json::json!(self.to_string())
+    }
+}
+
+// Times are in centiseconds, and multi is split into its parts
+impl JsonCell for ResultValue {
+    fn raw(&self) -> serde_json::Value {
+        match self {
+            ResultValue::Time(v) | ResultValue::Moves(v) => serde_json::json!(v),
+            ResultValue::Multi { time, solved, attempted } => serde_json::json!({ "solved": solved, "attempted": attempted, "seconds": time }),
+            ResultValue::DNF => serde_json::json!("DNF"),
+            ResultValue::DNS => serde_json::json!("DNS"),
+            ResultValue::None => serde_json::Value::Null,
+        }
+    }
+
+    // Multi uses the export's encoding (99 - points, seconds, missed), which sorts the same way
+    fn raw_csv(&self) -> Option<String> {
+        Some(match self {
+            ResultValue::Time(v) | ResultValue::Moves(v) => v.to_string(),
+            ResultValue::Multi { time, solved, attempted } => {
+                let missed = attempted - solved;
+                format!("{:02}{:05}{:02}", 99 - (solved - missed), time, missed)
+            },
+            ResultValue::DNF => "DNF".to_string(),
+            ResultValue::DNS => "DNS".to_string(),
+            ResultValue::None => "".to_string(),
+        })
+    }
+}
+
+impl<T: JsonCell> JsonCell for Option<T> {
+    fn raw(&self) -> serde_json::Value {
+        self.as_ref().map(|v| v.raw()).unwrap_or_default()
+    }
+
+    fn raw_csv(&self) -> Option<String> {
+        self.as_ref().and_then(|v| v.raw_csv())
+    }
+}
+
 pub trait Labelled {
     fn get_label(&self) -> String;
 }
 
-pub trait PageItem : Labelled + ToHtml {}
+pub trait PageItem : Labelled + TryToHtml + ToJson + ToCsv + Ranked {}
 
 #[derive(Clone)]
 pub struct RankRow<'a, S, D, const N: usize> where S: PartialOrd {
@@ -403,6 +748,7 @@ pub struct RankRow<'a, S, D, const N: usize> where S: PartialOrd {
     pub score: S,
     pub data: [D; N],
     pub person: &'a Cuber,
+    pub movement: Movement,
 }
 
 pub struct RankTable<'a, S, D, const N: usize> where S: PartialOrd + ToHtml, D: ToHtml {
@@ -417,45 +763,163 @@ impl<S: PartialOrd + ToHtml, D: ToHtml, const N: usize> Labelled for RankTable<'
     }
 }
 
-impl<S: PartialOrd + ToHtml, D: ToHtml, const N: usize> PageItem for RankTable<'_, S, D, N> {}
+impl<S: PartialOrd + JsonCell, D: JsonCell, const N: usize> PageItem for RankTable<'_, S, D, N> {}
 
-impl<S: PartialOrd + ToHtml, D: ToHtml, const N: usize> ToHtml for RankTable<'_, S, D, N> {
-    fn to_html_string(&self) -> String {
-        let title = &self.label;
-        let headers = self.headers.map(|s| format!("<th> {s} </th>")).join("");
-        let rowHtml = self.rows.iter().map(|row| {
-            let rowDataHtml = row
-                .data
-                .iter()
-                .map(|d| {let html = d.to_html_string(); format!("<td> {html} </td>") } )
-                .collect::<Vec<String>>()
-                .join("");
-            let rank = row.rank;
-            let name = &row.person.name;
-            let score = &row.score.to_html_string();
-            format!(r#"
-                <tr>  
-                    <td>{rank}</td>
-                    <td> {name} </td>
-                    <td> {score} </td>
-                    {rowDataHtml}
-                </tr> 
-                "#)
-        }).collect::<Vec<String>>().join("");
+// So one page can have tabs of different types (e.g. results and ratings)
+impl Labelled for Box<dyn PageItem + '_> {
+    fn get_label(&self) -> String {
+        (**self).get_label()
+    }
+}
 
-        format!(r#"
-            <table class="table table-striped" >
-                <tr>
-                    <th> Rank </th>
-                    <th> Competitor </th>
-                    <th> Result </th>
-                    {headers}
-                </tr>
-                {rowHtml}
-            </table>
-        "#)
+impl TryToHtml for Box<dyn PageItem + '_> {
+    fn try_to_html_string(&self) -> Result<String, String> {
+        (**self).try_to_html_string()
+    }
+}
+
+impl ToJson for Box<dyn PageItem + '_> {
+    fn to_json(&self) -> serde_json::Value {
+        (**self).to_json()
+    }
+}
+
+impl ToCsv for Box<dyn PageItem + '_> {
+    fn to_csv(&self) -> Option<String> {
+        (**self).to_csv()
+    }
+}
+
+impl Ranked for Box<dyn PageItem + '_> {
+    fn ranks(&self) -> Vec<(String, usize)> {
+        (**self).ranks()
+    }
+
+    fn set_movements(&mut self, previous: &FxHashMap<String, usize>) {
+        (**self).set_movements(previous)
+    }
+}
+
+impl PageItem for Box<dyn PageItem + '_> {}
+
+impl<S: PartialOrd + ToHtml, D: ToHtml, const N: usize> Ranked for RankTable<'_, S, D, N> {
+    fn ranks(&self) -> Vec<(String, usize)> {
+        self.rows.iter().map(|r| (r.person.id.clone(), r.rank)).collect()
+    }
+
+    fn set_movements(&mut self, previous: &FxHashMap<String, usize>) {
+        for row in self.rows.iter_mut() {
+            row.movement = Movement::between(previous.get(&row.person.id).copied(), row.rank);
+        }
+    }
+}
+
+impl<S: PartialOrd + JsonCell, D: JsonCell, const N: usize> ToJson for RankTable<'_, S, D, N> {
+    fn to_json(&self) -> serde_json::Value {
+        let rows = self.rows.iter().map(|row| serde_json::json!({
+            "rank": row.rank,
+            "movement": row.movement.raw(),
+            "id": row.person.id,
+            "name": row.person.name,
+            "score": row.score.cell_json(),
+            "data": self.headers.iter().zip(row.data.iter()).map(|(header, d)| {
+                let mut cell = d.cell_json();
+                cell["header"] = serde_json::json!(header);
+                cell
+            }).collect::<Vec<_>>(),
+        })).collect::<Vec<_>>();
+
+        serde_json::json!({ "label": self.label, "headers": self.headers.to_vec(), "rows": rows })
+    }
+}
+
+// Columns follow the headers, with a raw column (e.g. centiseconds) after each column of results
+impl<S: PartialOrd + JsonCell, D: JsonCell, const N: usize> ToCsv for RankTable<'_, S, D, N> {
+    fn to_csv(&self) -> Option<String> {
+        let score_raw = self.rows.iter().any(|r| r.score.raw_csv().is_some());
+        let data_raw = (0..N)
+            .map(|i| self.rows.iter().any(|r| r.data[i].raw_csv().is_some()))
+            .collect::<Vec<_>>();
+
+        let mut header = vec!["Rank".to_string(), "WCA ID".to_string(), "Name".to_string(), "Result".to_string()];
+        if score_raw {
+            header.push("Result (raw)".to_string());
+        }
+        for (h, raw) in self.headers.iter().zip(data_raw.iter()) {
+            header.push(h.to_string());
+            if *raw {
+                header.push(format!("{h} (raw)"));
+            }
+        }
+
+        let mut writer = csv::Writer::from_writer(Vec::new());
+        writer.write_record(&header).ok()?;
+        for row in self.rows.iter() {
+            let mut record = vec![row.rank.to_string(), row.person.id.clone(), row.person.name.clone(), row.score.formatted()];
+            if score_raw {
+                record.push(row.score.raw_csv().unwrap_or_default());
+            }
+            for (d, raw) in row.data.iter().zip(data_raw.iter()) {
+                record.push(d.formatted());
+                if *raw {
+                    record.push(d.raw_csv().unwrap_or_default());
+                }
+            }
+            writer.write_record(&record).ok()?;
+        }
+
+        String::from_utf8(writer.into_inner().ok()?).ok()
+    }
+}
+
+// Numbers sort as themselves. Anything else (DNFs, multi, competition names) means the column isn't sortable.
+fn sort_key<T: JsonCell>(value: &T) -> Result<Option<f64>, ()> {
+    match value.raw() {
+        serde_json::Value::Null => Ok(None),
+        serde_json::Value::Number(n) => Ok(n.as_f64()),
+        _ => Err(()),
     }
+}
+
+impl<S: PartialOrd + JsonCell, D: JsonCell, const N: usize> TryToHtml for RankTable<'_, S, D, N> {
+    fn try_to_html_string(&self) -> Result<String, String> {
+        // Optional data columns (e.g. WCA ranks) are left out when no row has anything in them
+        let shown = (0..N)
+            .map(|i| self.rows.iter().any(|r| !r.data[i].to_html_string().is_empty()))
+            .collect::<Vec<_>>();
+
+        let sortable = (0..N)
+            .map(|i| self.rows.iter().all(|r| sort_key(&r.data[i]).is_ok()))
+            .collect::<Vec<_>>();
+
+        let headers = self.headers
+            .iter()
+            .zip(shown.iter().zip(sortable.iter()))
+            .filter(|(_, (show, _))| **show)
+            .map(|(h, (_, sortable))| minijinja::context! { label => h, sortable })
+            .collect::<Vec<_>>();
 
+        // Cells are already HTML, but names go in as plain text for the template to escape
+        let rows = self.rows.iter().map(|row| minijinja::context! {
+            rank => row.rank,
+            movement => templates::html(row.movement.to_html_string()),
+            name => row.person.name,
+            profile => row.person.has_profile.then(|| format!("{}.html", progression::profile_page_name(&row.person.id))),
+            last_competed => row.person.last_competed.map(|d| d.to_string()),
+            score => templates::html(row.score.to_html_string()),
+            data => row.data
+                .iter()
+                .zip(shown.iter())
+                .filter(|(_, show)| **show)
+                .map(|(d, _)| minijinja::context! { 
+                    html => templates::html(d.to_html_string()), 
+                    sort => sort_key(d).ok().flatten(),
+                })
+                .collect::<Vec<_>>(),
+        }).collect::<Vec<_>>();
+
+        templates::render("rank_table.html", minijinja::context! { label => self.label, headers, rows })
+    }
 }
 
 
@@ -463,115 +927,257 @@ pub struct PageData {
     title: String,
     path: String,
 }
+
+// One entry in docs/pages.json
+#[derive(serde::Serialize, serde::Deserialize)]
+struct ManifestEntry {
+    name: String,
+    title: String,
+    html: String,
+    json: String,
+    listed: bool, // Whether it's linked from the homepage
+}
 pub struct Site {
     pages: Vec<PageData>,
     web_url: String,
+    root: String, // Directory index.html (and docs/) get written to
+    title: String,
+    history: RankHistory,
+    manifest: Vec<ManifestEntry>,
+    built: Date, // What "recently active" is measured from
+    assets: Assets,
 }
 
 impl Site {
-    pub fn new(web_url: &str) -> Self {
+    pub fn new(web_url: &str, root: &str, title: &str, built: Date, assets: Assets) -> Self {
         Site {
             pages: Vec::<PageData>::new(),
             web_url: web_url.to_string(),
+            root: root.to_string(),
+            title: title.to_string(),
+            history: RankHistory::load(&Self::snapshot_path(root)),
+            manifest: Vec::new(),
+            built,
+            assets,
         }
     }
 
-    pub fn to_html_file<T>(&mut self, page: &RankPage<T>) where T:PageItem {
-        let path = format!("docs/{}.html", page.name);
-        let mut output = std::fs::File::create( path.to_string() );
-    
-        let title = &page.title;
+    fn snapshot_path(root: &str) -> String {
+        format!("{root}/docs/ranks.json")
+    }
 
-        self.pages.push(PageData { title: title.to_string(), path: path });
-    
-        let tables = page.tables.iter()
-        .map(|i| format!(r#"<div class="tab-pane fade" id="{}" role="tabpanel" tabindex="0">"#, i.get_label()).to_string() + &i.to_html_string() + "</div>")
-        .collect::<Vec<String>>()
-        .join("\n");
-    
-        let tabs = page.tables.iter()
-        .enumerate()
-        .map(|(idx, tab)| format!(r##"  
-            <li class="nav-item" role="presentation">
-                <button class="nav-link" id="home-tab" data-bs-toggle="tab" data-bs-target="#{}" type="button" role="tab" aria-controls="home-tab-pane" aria-selected="{}">{}</button>
-            </li>
-            "##, tab.get_label(), (idx == 0).to_string(), tab.get_label())
-        )
-        .collect::<Vec<String>>()
-        .join("\n");
-        
-        let page = format!(r#"
-        <html>
-            <head>
-                <title> {title} </title>
-                <link rel="stylesheet" href="https://cdn.jsdelivr.net/npm/bootstrap-icons@1.10.5/font/bootstrap-icons.css">
-                <link href="https://cdn.jsdelivr.net/npm/bootstrap@5.3.1/dist/css/bootstrap.min.css" rel="stylesheet" crossorigin="anonymous"></link>
-                <script src="https://cdn.jsdelivr.net/npm/bootstrap@5.3.1/dist/js/bootstrap.bundle.min.js" integrity="sha384-HwwvtgBNo3bZJJLYd8oVXjrBZt8cqVSpeBNS5n7C8IVInixGAoxmnlMuBnhbgrkm" crossorigin="anonymous"></script>
-            </head>
-            <body data-bs-theme="dark" class="p-5">
-                <h1> {title} </h1> 
-                <a href="../index.html" class="fs-2 btn btn-secondary m-4 position-fixed top-0 end-0" >
-                    <i class="bi bi-house-door-fill"></i>
-                </a>
-                <ul class="nav nav-tabs role="tablist">
-                    {tabs}
-                </ul>
-                <div class="tab-content">
-                    {tables}
-                </div>
-            </body>
-        </html>
-        "#);
-    
-        if output.is_ok() {
-            output.unwrap().write_all(page.as_bytes());
-        }
+    pub fn to_html_file<T>(&mut self, page: &mut RankPage<T>) -> Result<(), String> where T:PageItem {
+        let path = self.write_page(page, true)?;
+        self.pages.push(PageData { title: page.title.to_string(), path });
+        Ok(())
     }
 
-    pub fn gen_homepage(&self) {
-        let mut output = std::fs::File::create( "index.html" );   
+    // For pages only reachable through another page (e.g. via a selector), so left off the homepage
+    pub fn to_unlisted_html_file<T>(&mut self, page: &mut RankPage<T>) -> Result<(), String> where T:PageItem {
+        self.write_page(page, false)?;
+        Ok(())
+    }
 
-        let links = self.pages
-        .iter()
-        .map(|p| format!(r##"<a href="{}" class="list-group-item list-group-item-action">{}</a>"##, p.path, p.title, ))
-        .collect::<Vec<_>>()
-        .join("\n");
-
-        let page = format!(r#"
-        <html>
-            <head>
-                <title> WA Speedcubing Statistics </title>
-                <link rel="stylesheet" href="https://cdn.jsdelivr.net/npm/bootstrap-icons@1.10.5/font/bootstrap-icons.css">
-                <link href="https://cdn.jsdelivr.net/npm/bootstrap@5.3.1/dist/css/bootstrap.min.css" rel="stylesheet" crossorigin="anonymous"></link>
-                <script src="https://cdn.jsdelivr.net/npm/bootstrap@5.3.1/dist/js/bootstrap.bundle.min.js" integrity="sha384-HwwvtgBNo3bZJJLYd8oVXjrBZt8cqVSpeBNS5n7C8IVInixGAoxmnlMuBnhbgrkm" crossorigin="anonymous"></script>
-            </head>
-            <body data-bs-theme="dark" class="p-5">
-                <h1> WA Speedcubing Statistics </h1> 
-                <div class="list-group list-group-flush">
-                    {links}
-                </div>
-            </body>
-        </html>
-        "#);
-    
-        if output.is_ok() {
-            output.unwrap().write_all(page.as_bytes());
+    // Saves this build's ranks so the next build can show how they moved
+    pub fn save_rank_snapshot(&self) -> std::io::Result<()> {
+        self.history.save(&Self::snapshot_path(&self.root))
+    }
+
+    // Lists every page written so far, along with where its HTML and JSON are. Pages from earlier builds are kept
+    // while their HTML is still there, since psych sheets and predictions are built without the rest of the site.
+    pub fn save_manifest(&self) -> std::io::Result<()> {
+        let path = format!("{}/docs/pages.json", self.root);
+        let earlier = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|s| serde_json::from_str::<Vec<ManifestEntry>>(&s).ok())
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|e| !self.manifest.iter().any(|m| m.name == e.name))
+            .filter(|e| std::path::Path::new(&format!("{}/{}", self.root, e.html)).exists())
+            .collect::<Vec<_>>();
+
+        std::fs::write(path, serde_json::to_string(&earlier.iter().chain(self.manifest.iter()).collect::<Vec<_>>())?)
+    }
+
+    // Returns the path of the page relative to the site root, or why it couldn't be rendered or written
+    fn write_page<T>(&mut self, page: &mut RankPage<T>, listed: bool) -> Result<String, String> where T:PageItem {
+        self.history.diff(page);
+
+        let selector = page.selector.as_ref().map(|s| s.to_html_string(&page.name)).unwrap_or_default();
+
+        // Tab ids end up in links (e.g. 333.html#average), so two labels that look alike mustn't share one
+        let mut ids = FxHashSet::default();
+        let mut csvs = Vec::new(); // (file name relative to the page, contents)
+        let tabs = page.tables.iter().map(|tab| {
+            let base = tab_id(&tab.get_label());
+            let id = (1..).map(|n| if n == 1 { base.clone() } else { format!("{base}-{n}") }).find(|id| !ids.contains(id)).unwrap_or(base);
+            ids.insert(id.clone());
+
+            let csv = tab.to_csv().map(|contents| {
+                let file = format!("{}_{id}.csv", page.name);
+                csvs.push((file.clone(), contents));
+                file
+            });
+
+            Ok(minijinja::context! {
+                label => tab.get_label(),
+                csv,
+                html => templates::html(tab.try_to_html_string()?),
+                id,
+            })
+        }).collect::<Result<Vec<_>, String>>()?;
+
+        let html = templates::render("page.html", minijinja::context! { 
+            title => page.title, 
+            built => self.built.to_string(), 
+            assets => self.assets.links(""),
+            selector => templates::html(selector), 
+            tabs 
+        })?;
+
+        // Nothing is written until the page has rendered, so a broken template doesn't leave its JSON or CSVs behind
+        let write = |file: &str, contents: String| {
+            std::fs::write(format!("{}/{file}", self.root), contents).map_err(|e| format!("Couldn't write {file}: {e}"))
+        };
+
+        let path = format!("docs/{}.html", page.name);
+        let json_path = format!("docs/{}.json", page.name);
+        let json = serde_json::json!({
+            "name": page.name,
+            "title": page.title,
+            "tables": page.tables.iter().map(|t| t.to_json()).collect::<Vec<_>>(),
+        });
+        write(&json_path, serde_json::to_string(&json).map_err(|e| format!("Couldn't write {json_path}: {e}"))?)?;
+        for (file, contents) in csvs {
+            write(&format!("docs/{file}"), contents)?;
         }
+        write(&path, html)?;
+
+        self.manifest.push(ManifestEntry {
+            name: page.name.clone(),
+            title: page.title.clone(),
+            html: path.clone(),
+            json: json_path,
+            listed,
+        });
+
+        Ok(path)
+    }
+
+    pub fn gen_homepage(&self) -> Result<(), String> {
+        let pages = self.pages
+        .iter()
+        .map(|p| minijinja::context! { title => p.title, path => p.path })
+        .collect::<Vec<_>>();
+
+        let page = templates::render("homepage.html", minijinja::context! { title => self.title, assets => self.assets.links("docs/"), pages })?;
+        std::fs::write(format!("{}/index.html", self.root), page).map_err(|e| format!("Couldn't write index.html: {e}"))
     }
 
 }
 
 
 
+// Labels can have spaces/brackets, which aren't allowed in ids (or usable in selectors)
+fn html_id(label: &str) -> String {
+    label
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect()
+}
+
+// A tab's id, also used for its URL hash and CSV name, e.g. "Single (last 12 months)" -> "single-last-12-months"
+fn tab_id(label: &str) -> String {
+    let id = html_id(label).to_lowercase().split('-').filter(|s| !s.is_empty()).collect::<Vec<_>>().join("-");
+    if id.is_empty() { "tab".to_string() } else { id }
+}
+
 pub struct RankPage<T> where T: PageItem {
     pub title: String,
     pub name: String,
-    pub tables: Vec<T> 
+    pub tables: Vec<T>,
+    pub selector: Option<PageSelector>,
+}
+
+// Dropdown for jumping between variants of the same page (e.g. SOR for each year)
+#[derive(Clone)]
+pub struct PageSelector {
+    pub label: String,
+    pub pages: Vec<(String, String)>, // (display text, page name)
+}
+
+impl PageSelector {
+    fn to_html_string(&self, current: &str) -> String {
+        let label = escape::text(&self.label);
+        let selected = escape::text(self.pages
+            .iter()
+            .find(|(_, name)| name == current)
+            .map(|(text, _)| text.as_str())
+            .unwrap_or(""));
+
+        let items = self.pages
+            .iter()
+            .map(|(text, name)| {
+                let active = if name == current { " active" } else { "" };
+                format!(r#"<li><a class="dropdown-item{active}" href="{}.html">{}</a></li>"#, escape::attr(name), escape::text(text))
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        format!(r#"
+            <div class="dropdown mb-3">
+                <button class="btn btn-secondary dropdown-toggle" type="button" data-bs-toggle="dropdown" aria-expanded="false">
+                    {label}: {selected}
+                </button>
+                <ul class="dropdown-menu">
+                    {items}
+                </ul>
+            </div>
+        "#)
+    }
+}
+
+pub struct EventRanks<'a> {
+    pub event: Event,
+    pub single: Vec<RankRow<'a, ResultValue, usize, 0>>,
+    pub average: Vec<RankRow<'a, ResultValue, usize, 0>>,
+}
+
+impl<'a> EventRanks<'a> {
+    pub fn new(cubers: &'a [Cuber], event: Event) -> Self {
+        let mut single = cubers.iter()
+            .map(|c| RankRow { score: c.get_single(event), rank: 0, data: [0; 0], person: c, movement: Movement::Unknown } )
+            .collect::<Vec<_>>();
+        let mut average = cubers.iter()
+            .map(|c| RankRow { score: c.get_average(event), rank: 0, data: [0; 0], person: c, movement: Movement::Unknown } )
+            .collect::<Vec<_>>();
+        rank(&mut single, true);
+        rank(&mut average, true);
+
+        EventRanks { event, single, average }
+    }
+
+    // Best result in the ranking (None if nobody has one)
+    pub fn single_record(&self) -> ResultValue {
+        self.single.first().map(|r| r.score).unwrap_or_default()
+    }
+
+    pub fn average_record(&self) -> ResultValue {
+        self.average.first().map(|r| r.score).unwrap_or_default()
+    }
 }
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
+    let options = Options::from_args(std::env::args().skip(1))?;
+    templates::load(options.templates.as_deref())?;
+
+    // Read before downloading anything so a bad file fails fast
+    let wcif = options.psych_sheet.as_ref().map(|path| wcif::Wcif::load(path)).transpose()?;
+    let predict_wcifs = options.predict.iter().map(|path| wcif::Wcif::load(path)).collect::<Result<Vec<_>, _>>()?;
+    let mut provisional_comps = options.provisional.iter().map(|path| ProvisionalComp::load(path)).collect::<Result<Vec<_>, _>>()?;
+
     let info = reqwest::get("https://www.worldcubeassociation.org/api/v0/export/public")
         .await?
         .json::<serde_json::Value>()
@@ -587,10 +1193,43 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     zip.extract("./data");
 
     println!("Getting WA Comps");
-    let wa_comps = WCACompetition::Read()?
+    let mut all_comps = WCACompetition::Read()?
+        .into_iter()
+        .filter_map(|c| c.ok())
+        .collect::<Vec::<WCACompetition>>();
+
+    // The export usually lists a comp well before its results are in, so only add the ones it doesn't know about
+    let export_comp_ids = FxHashSet::from_iter(all_comps.iter().map(|c| c.id.clone()));
+    all_comps.extend(
+        provisional_comps
+        .iter()
+        .filter(|p| !export_comp_ids.contains(&p.competition.id))
+        .map(|p| p.competition.clone())
+    );
+    all_comps.retain(|c| options.as_of.is_none_or(|date| c.end_date().is_some_and(|end| end <= date)));
+
+    // Needed for every competition (not just WA ones) since residents' interstate results are kept too
+    let comp_years = FxHashMap::from_iter(
+        all_comps
+        .iter()
+        .map(|c| (c.id.clone(), c.year.parse::<usize>().unwrap_or(0)))
+    );
+
+    let comp_dates = FxHashMap::from_iter(
+        all_comps
+        .iter()
+        .filter_map(|c| c.end_date().map(|d| (c.id.clone(), d)))
+    );
+
+    let comp_names = FxHashMap::from_iter(
+        all_comps
+        .iter()
+        .map(|c| (c.id.clone(), c.name.clone()))
+    );
+
+    let wa_comps = all_comps
         .into_iter()
-        .filter(|c| c.as_ref().is_ok_and(|v| v.cityName.contains("Western Australia") ))
-        .map(|c| c.unwrap())
+        .filter(|c| c.cityName.contains("Western Australia"))
         .collect::<Vec::<WCACompetition>>();
 
     let wa_comp_id_hash = FxHashSet::from_iter(
@@ -601,12 +1240,39 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     println!("Getting WA Results");
 
-    let all_wa_results = WCAResult::Read()?
+    let mut all_wa_results = WCAResult::Read()?
         .into_iter()
         .filter(|r| r.as_ref().is_ok_and(|v| wa_comp_id_hash.contains(&v.competitionId) ))
         .map(|r| r.unwrap())
         .collect::<Vec::<WCAResult>>();
 
+    // Provisional results drop out by themselves once the export has the real ones
+    let exported_comp_ids = FxHashSet::from_iter(all_wa_results.iter().map(|r| r.competitionId.clone()));
+    provisional_comps.retain(|p| {
+        let id = &p.competition.id;
+        let skipped = if exported_comp_ids.contains(id) {
+            Some("it's in the export now")
+        }
+        else if options.as_of.is_some() && !comp_dates.contains_key(id) {
+            Some("it ends after the --as-of date")
+        }
+        else if !wa_comp_id_hash.contains(id) {
+            Some("the export lists it outside WA")
+        }
+        else {
+            None
+        };
+        if let Some(reason) = skipped {
+            println!("Ignoring the provisional results from {id}, since {reason}");
+        }
+        skipped.is_none()
+    });
+    for comp in provisional_comps.iter() {
+        println!("Adding provisional results from {}", comp.competition.name);
+        all_wa_results.extend(comp.results.iter().cloned());
+    }
+    let provisional = Provisional::new(&provisional_comps);
+
     println!("Sorting Results By Person");
     
     let mut wa_results_by_person = FxHashMap::<String, Vec<WCAResult>>::default();
@@ -629,7 +1295,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         r.as_ref()
         .is_ok_and(|v| 
             wa_results_by_person.contains_key(&v.personId) && 
-            !wa_comp_id_hash.contains(&v.competitionId)
+            !wa_comp_id_hash.contains(&v.competitionId) &&
+            comp_years.contains_key(&v.competitionId) // Skips comps after --as-of
         )
     )
     .map(|r| r.unwrap())
@@ -640,9 +1307,13 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             .push(result);
     }
 
+    println!("Calculating Competition Statistics");
+
+    let competition_tables = comp_stats::competition_tables(&wa_comps, &wa_results_by_person, &comp_dates);
+
     println!("Filtering IDs by proportion of WA competitions");
 
-    let wa_cubers = wa_results_by_person
+    let mut wa_cubers = wa_results_by_person
     .into_iter()
     .filter(|(id, results)| -> bool {
         let wa_count = results.iter().filter(|r| wa_comp_id_hash.contains(&r.competitionId) ).count();
@@ -655,25 +1326,443 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     println!("Total:{}", wa_cubers.len());
 
-    let mut site = Site::new("");
+    if options.womens_rankings {
+        println!("Getting Genders");
 
-    let mut single_sor_hashmap = FxHashMap::from_iter(
-        wa_cubers.iter()
-        .map(|c|
-            (
-                c.id.to_owned(),
-                RankRow {
-                    rank: 0,
-                    score: 0,
-                    data: [SORRank::Blank; 18],
-                    person: c
-                }
-            )
-        )
+        let genders = FxHashMap::from_iter(
+            WCAPerson::Read()?
+            .filter_map(|p| p.ok())
+            .map(|p| (p.id, p.gender))
+        );
+
+        for cuber in wa_cubers.iter_mut() {
+            cuber.gender = genders.get(&cuber.id).cloned().unwrap_or_default();
+        }
+    }
+
+    let root = options.output_root();
+    std::fs::create_dir_all(format!("{root}/docs"))?;
+    let title = match options.as_of {
+        Some(date) => format!("WA Speedcubing Statistics (as of {date})"),
+        None => "WA Speedcubing Statistics".to_string(),
+    };
+    for cuber in wa_cubers.iter_mut() {
+        cuber.last_competed = cuber.competition_dates(&comp_dates).last().copied();
+    }
+
+    options.assets.write(&root).await?;
+    let mut site = Site::new("", &root, &title, options.as_of.unwrap_or_else(Date::today), options.assets);
+
+    if let Some(wcif) = wcif {
+        println!("Building Psych Sheet For {}", wcif.name);
+
+        let registrants = psych_sheet::registrants(&wcif, &comp_dates)?;
+        let wca_ranks = WCARanks::load(&FxHashSet::from_iter(registrants.iter().map(|(c, _)| c.id.clone())))?;
+        site.to_unlisted_html_file(&mut psych_sheet::psych_sheet_page(&wcif, &registrants, &wa_cubers, &wca_ranks))?;
+
+        println!("Written to {root}/docs/psych_{}.html", wcif.id);
+        site.save_manifest()?;
+        return Ok(());
+    }
+
+    if !predict_wcifs.is_empty() {
+        let mut outcomes = predictions::Outcomes::new();
+
+        for wcif in predict_wcifs {
+            println!("Predicting Podiums For {}", wcif.name);
+
+            let registrants = psych_sheet::registrants(&wcif, &comp_dates)?;
+            let (mut page, comp_outcomes) = predictions::predictions_page(&wcif, &registrants, &comp_dates, options.recent_months);
+            site.to_unlisted_html_file(&mut page)?;
+            outcomes.extend(comp_outcomes);
+
+            println!("Written to {root}/docs/predictions_{}.html", wcif.id);
+        }
+
+        // Scoring across every comp that has already happened says more than any one comp can
+        if !outcomes.is_empty() {
+            site.to_unlisted_html_file(&mut RankPage {
+                name: "predictions_calibration".to_string(),
+                title: "Podium Prediction Calibration".to_string(),
+                tables: vec![predictions::calibration_table(&outcomes)],
+                selector: None,
+            })?;
+            println!("Written to {root}/docs/predictions_calibration.html");
+        }
+        site.save_manifest()?;
+        return Ok(());
+    }
+
+    println!("Drawing Progression Charts");
+    for cuber in wa_cubers.iter_mut() {
+        site.to_unlisted_html_file(&mut progression::profile_page(cuber, &comp_dates, &comp_names))?;
+        cuber.has_profile = true;
+    }
+
+    // The export's ranks are for today's PBs, so they'd be wrong next to an --as-of ranking
+    let wca_ranks = if options.as_of.is_none() {
+        println!("Getting WCA Ranks");
+        WCARanks::load(&FxHashSet::from_iter(wa_cubers.iter().map(|c| c.id.clone())))?
+    }
+    else {
+        WCARanks::default()
+    };
+
+    let event_ranks = Event::iter()
+        .map(|e| EventRanks::new(&wa_cubers, *e))
+        .collect::<Vec<_>>();
+
+    let (single_sor, average_sor) = sum_of_ranks(&wa_cubers, &event_ranks);
+    let kinch_rows = kinch_ranks(&wa_cubers, &event_ranks);
+
+    println!("Calculating Recent Form");
+
+    let recent_months = options.recent_months;
+    let recent_cutoff = options.as_of.unwrap_or_else(Date::today).months_before(recent_months);
+    let recent_cubers = wa_cubers.iter()
+        .filter_map(|c| c.filtered(|r| comp_dates.get(&r.competitionId).is_some_and(|d| *d > recent_cutoff)))
+        .collect::<Vec<_>>();
+
+    let recent_ranks = Event::iter()
+        .map(|e| EventRanks::new(&recent_cubers, *e))
+        .collect::<Vec<_>>();
+
+    let (recent_single_sor, recent_average_sor) = sum_of_ranks(&recent_cubers, &recent_ranks);
+    let recent_kinch_rows = kinch_ranks(&recent_cubers, &recent_ranks);
+
+    // Empty (and left off the site) when women's rankings are turned off
+    let womens_cubers = wa_cubers.iter()
+        .filter(|c| c.is_female())
+        .cloned()
+        .collect::<Vec<_>>();
+
+    let womens_ranks = Event::iter()
+        .map(|e| EventRanks::new(&womens_cubers, *e))
+        .collect::<Vec<_>>();
+
+    let (womens_single_sor, womens_average_sor) = sum_of_ranks(&womens_cubers, &womens_ranks);
+    let womens_kinch_rows = kinch_ranks(&womens_cubers, &womens_ranks);
+
+    // Every calendar year that a WA resident competed in, newest first
+    let mut years = wa_cubers.iter()
+        .flat_map(|c| c.results.iter().filter_map(|r| comp_years.get(&r.competitionId).copied()))
+        .collect::<FxHashSet<_>>()
+        .into_iter()
+        .collect::<Vec<_>>();
+    years.sort_by(|a, b| b.cmp(a));
+
+    // Event pages keep their recent form and women's rankings in tabs rather than separate pages
+    let womens_rankings = options.womens_rankings;
+    let year_selector = |name: &str, variant_pages: bool| PageSelector {
+        label: "Year".to_string(),
+        pages: std::iter::once(("All time".to_string(), name.to_string()))
+            .chain(variant_pages.then(|| (format!("Last {recent_months} months"), format!("{name}_recent"))))
+            .chain((variant_pages && womens_rankings).then(|| ("Women's".to_string(), format!("{name}_womens"))))
+            .chain(years.iter().map(|y| (y.to_string(), format!("{name}_{y}"))))
+            .collect()
+    };
+
+    println!("Calculating Ratings");
+    std::fs::create_dir_all(format!("{root}/docs/ratings"))?;
+
+    for ((ranks, recent), womens) in event_ranks.into_iter().zip(recent_ranks).zip(womens_ranks) {
+        let event = ranks.event;
+        let ratings = ratings::rate_event(&wa_cubers, event, &wa_comp_id_hash, &comp_dates, options.elo);
+        ratings::write_history(&ratings, &format!("{root}/docs/ratings/{}.json", event.to_string()))?;
+
+        // Charted in the order of the ranking the psych sheet would seed by
+        let top = if psych_sheet::seeded_by_single(event) { &ranks.single } else { &ranks.average };
+        let top = top.iter().filter(|r| r.score.valid()).map(|r| r.person).collect::<Vec<_>>();
+        let progression = progression::top_ten_tab(top.into_iter(), event, &comp_dates, &comp_names);
+
+        // Multi has no averages, so it gets its singles instead
+        let distribution = if ranks.average.iter().any(|r| r.score.valid()) {
+            histogram::Histogram::new("Average Distribution", event, &ranks.average, true)
+        }
+        else {
+            histogram::Histogram::new("Single Distribution", event, &ranks.single, false)
+        };
+
+        let mut page = RankPage {
+            name:  event.to_string(), 
+            title: provisional.title(format!("WA {} Rankings", event.to_nice_str()), Some(event), None), 
+            tables: vec![
+                Box::new(wca_ranks.single_table(event, "Single", ranks.single)) as Box<dyn PageItem>,
+                Box::new(wca_ranks.average_table(event, "Average", ranks.average)),
+                // WCA ranks are for all-time PBs, so they don't belong next to recent results
+                Box::new(WCARanks::default().single_table(event, &format!("Single (last {recent_months} months)"), recent.single)),
+                Box::new(WCARanks::default().average_table(event, &format!("Average (last {recent_months} months)"), recent.average)),
+            ],
+            selector: Some(year_selector(&event.to_string(), false)),
+        };
+
+        if womens_rankings {
+            // Nor are they women's ranks
+            page.tables.push(Box::new(WCARanks::default().single_table(event, "Women's Single", womens.single)));
+            page.tables.push(Box::new(WCARanks::default().average_table(event, "Women's Average", womens.average)));
+        }
+
+        page.tables.push(Box::new(ratings::rating_table(&ratings)));
+        page.tables.push(Box::new(distribution));
+        page.tables.push(Box::new(progression));
+
+        site.to_html_file(&mut page)?;
+    }
+
+    site.to_html_file(&mut sor_page("sor", &provisional.title("WA Sum Of Ranks".to_string(), None, None), single_sor, average_sor, Some(year_selector("sor", true))))?;
+    site.to_html_file(&mut kinch_page("kinch", &provisional.title("WA Kinch Ranks".to_string(), None, None), kinch_rows, Some(year_selector("kinch", true))))?;
+    site.to_unlisted_html_file(&mut sor_page(
+        "sor_recent", 
+        &provisional.title(format!("WA Sum Of Ranks (last {recent_months} months)"), None, None), 
+        recent_single_sor, 
+        recent_average_sor, 
+        Some(year_selector("sor", true))
+    ))?;
+    site.to_unlisted_html_file(&mut kinch_page(
+        "kinch_recent", 
+        &provisional.title(format!("WA Kinch Ranks (last {recent_months} months)"), None, None), 
+        recent_kinch_rows, 
+        Some(year_selector("kinch", true))
+    ))?;
+
+    if womens_rankings {
+        site.to_unlisted_html_file(&mut sor_page(
+            "sor_womens", 
+            &provisional.title("WA Women's Sum Of Ranks".to_string(), None, None), 
+            womens_single_sor, 
+            womens_average_sor, 
+            Some(year_selector("sor", true))
+        ))?;
+        site.to_unlisted_html_file(&mut kinch_page(
+            "kinch_womens", 
+            &provisional.title("WA Women's Kinch Ranks".to_string(), None, None), 
+            womens_kinch_rows, 
+            Some(year_selector("kinch", true))
+        ))?;
+    }
+
+    // Newcomer retention, plus a rookie of the year ranking for each cohort
+    let cohort_selector = PageSelector {
+        label: "Cohort".to_string(),
+        pages: std::iter::once(("Retention".to_string(), "cohorts".to_string()))
+            .chain(years.iter().map(|y| (y.to_string(), format!("rookies_{y}"))))
+            .collect()
+    };
+
+    site.to_html_file(&mut RankPage {
+        name: "cohorts".to_string(),
+        title: provisional.title("WA Newcomer Cohorts".to_string(), None, None),
+        tables: vec![cohorts::retention_table(&wa_cubers, &comp_dates)],
+        selector: Some(cohort_selector.clone()),
+    })?;
+
+    let first_comp_years = FxHashMap::from_iter(
+        wa_cubers.iter().filter_map(|c| c.first_competition(&comp_dates).map(|d| (c.id.as_str(), d.year)))
     );
 
-    let mut average_sor_hashmap = FxHashMap::from_iter(
-        wa_cubers.iter()
+    println!("Calculating Yearly Rankings");
+
+    // Each event's (single, average) results from the year before, oldest year first so it's always at hand
+    let mut last_year: Option<(usize, Vec<(YearResults, YearResults)>)> = None;
+
+    for year in years.iter().rev() {
+        // Only results (and therefore PBs and records) from competitions held in this year count
+        let year_cubers = wa_cubers.iter()
+            .filter_map(|c| c.filtered(|r| comp_years.get(&r.competitionId) == Some(year)))
+            .collect::<Vec<_>>();
+
+        let year_ranks = Event::iter()
+            .map(|e| EventRanks::new(&year_cubers, *e))
+            .collect::<Vec<_>>();
+
+        let (single_sor, average_sor) = sum_of_ranks(&year_cubers, &year_ranks);
+        let kinch_rows = kinch_ranks(&year_cubers, &year_ranks);
+
+        // Rookies are ranked by their Kinch score against everyone that year
+        let mut rookie_rows = kinch_rows
+            .iter()
+            .filter(|r| first_comp_years.get(r.person.id.as_str()) == Some(year))
+            .cloned()
+            .collect::<Vec<_>>();
+        rank(&mut rookie_rows, false);
+
+        site.to_unlisted_html_file(&mut kinch_page(
+            &format!("rookies_{year}"), 
+            &provisional.title(format!("WA {year} Rookie Of The Year"), None, Some(*year)), 
+            rookie_rows, 
+            Some(cohort_selector.clone())
+        ))?;
+
+        site.to_unlisted_html_file(&mut sor_page(
+            &format!("sor_{year}"), 
+            &provisional.title(format!("WA {year} Sum Of Ranks"), None, Some(*year)), 
+            single_sor, 
+            average_sor, 
+            Some(year_selector("sor", true))
+        ))?;
+        site.to_unlisted_html_file(&mut kinch_page(
+            &format!("kinch_{year}"), 
+            &provisional.title(format!("WA {year} Kinch Ranks"), None, Some(*year)), 
+            kinch_rows, 
+            Some(year_selector("kinch", true))
+        ))?;
+
+        let previous = last_year
+            .as_ref()
+            .filter(|(y, _)| y + 1 == *year)
+            .map(|(_, results)| results);
+        let mut this_year = Vec::new();
+
+        for ranks in year_ranks {
+            let event = ranks.event;
+            let single_results = year_results(&ranks.single);
+            let average_results = year_results(&ranks.average);
+
+            let mut page = RankPage {
+                name: format!("{}_{year}", event.to_string()),
+                title: provisional.title(format!("WA {year} {} Rankings", event.to_nice_str()), Some(event), Some(*year)),
+                tables: vec![
+                    year_table("Single", ranks.single, previous.map(|p| &p[event as usize].0)),
+                    year_table("Average", ranks.average, previous.map(|p| &p[event as usize].1)),
+                ],
+                selector: Some(year_selector(&event.to_string(), false)),
+            };
+            site.to_unlisted_html_file(&mut page)?;
+
+            this_year.push((single_results, average_results));
+        }
+
+        last_year = Some((*year, this_year));
+    }
+        
+    println!("Calculating Consistency");
+
+    let consistency_selector = PageSelector {
+        label: "Event".to_string(),
+        pages: Event::iter()
+            .map(|e| (e.to_nice_str().to_string(), format!("consistency_{}", e.to_string())))
+            .collect()
+    };
+
+    for event in Event::iter() {
+        let mut page = RankPage {
+            name: format!("consistency_{}", event.to_string()),
+            title: provisional.title(format!("WA {} Consistency", event.to_nice_str()), Some(*event), None),
+            tables: consistency::consistency_tables(&wa_cubers, *event, options.min_attempts),
+            selector: Some(consistency_selector.clone()),
+        };
+
+        // The rest are reached through the selector
+        if *event == Event::_3 {
+            site.to_html_file(&mut page)?;
+        }
+        else {
+            site.to_unlisted_html_file(&mut page)?;
+        }
+    }
+
+    println!("Calculating Head To Head Records");
+
+    head_to_head::write_data(&wa_cubers, &comp_names, &comp_dates, &format!("{root}/docs/h2h"))?;
+    site.to_html_file(&mut RankPage {
+        name: "h2h".to_string(),
+        title: provisional.title("WA Head To Head".to_string(), None, None),
+        tables: vec![head_to_head::HeadToHead],
+        selector: None,
+    })?;
+
+    println!("Calculating Milestones");
+
+    let milestone_selector = PageSelector {
+        label: "Milestone".to_string(),
+        pages: std::iter::once(("Achievers Over Time".to_string(), "milestones".to_string()))
+            .chain(Event::MILESTONES.iter().map(|m| (m.label.to_string(), milestones::page_name(m))))
+            .collect()
+    };
+
+    site.to_html_file(&mut RankPage {
+        name: "milestones".to_string(),
+        title: provisional.title("WA Milestones".to_string(), None, None),
+        tables: vec![milestones::achievers_over_time(&wa_cubers, &comp_dates)],
+        selector: Some(milestone_selector.clone()),
+    })?;
+
+    for milestone in Event::MILESTONES.iter() {
+        site.to_unlisted_html_file(&mut RankPage {
+            name: milestones::page_name(milestone),
+            title: provisional.title(format!("WA {}", milestone.label), Some(milestone.event), None),
+            tables: vec![milestones::first_achievements(&wa_cubers, milestone, &comp_dates, &comp_names)],
+            selector: Some(milestone_selector.clone()),
+        })?;
+    }
+
+    site.to_html_file(&mut RankPage {
+        name: "competitions".to_string(),
+        title: provisional.title("WA Competition Statistics".to_string(), None, None),
+        tables: competition_tables,
+        selector: None,
+    })?;
+
+    site.gen_homepage()?;
+    site.save_rank_snapshot()?;
+    site.save_manifest()?;
+
+    Ok(())
+}
+
+#[derive(Clone, Copy)]
+enum SORRank {
+    Blank,
+    Normal(usize),
+    Default(usize)
+}
+
+impl SORRank {
+    pub fn get_value(&self) -> usize {
+        match self {
+            Self::Blank => 0,
+            Self::Normal(n) => *n,
+            Self::Default(n) => *n
+        }
+    }
+}
+
+impl ToHtml for SORRank {
+    fn to_html_string(&self) -> String {
+        match self {
+            SORRank::Blank => "<span> </span>".to_string(),
+            SORRank::Normal(r) => format!("<span> {} </span>", r),
+            SORRank::Default(r) => format!("<span style=\"color: var(--bs-orange);\"> {} </span>", r)
+        }
+    }
+}
+
+impl JsonCell for SORRank {
+    fn raw(&self) -> serde_json::Value {
+        match self {
+            SORRank::Blank => serde_json::Value::Null,
+            SORRank::Normal(r) | SORRank::Default(r) => serde_json::json!(r),
+        }
+    }
+
+    fn formatted(&self) -> String {
+        match self {
+            SORRank::Blank => "".to_string(),
+            SORRank::Normal(r) | SORRank::Default(r) => r.to_string(),
+        }
+    }
+
+    // Defaults are the rank given to people without a result, which the site shows in orange
+    fn cell_json(&self) -> serde_json::Value {
+        serde_json::json!({ "raw": self.raw(), "formatted": self.formatted(), "default": matches!(self, SORRank::Default(_)) })
+    }
+}
+
+type SORRow<'a> = RankRow<'a, usize, SORRank, 18>;
+
+fn sum_of_ranks<'a>(cubers: &'a [Cuber], event_ranks: &[EventRanks<'a>]) -> (Vec<SORRow<'a>>, Vec<SORRow<'a>>) {
+    let mut single_sor_hashmap = FxHashMap::from_iter(
+        cubers.iter()
         .map(|c|
             (
                 c.id.to_owned(),
@@ -681,75 +1770,43 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     rank: 0,
                     score: 0,
                     data: [SORRank::Blank; 18],
-                    person: c
+                    person: c,
+                    movement: Movement::Unknown,
                 }
             )
         )
     );
 
-    let mut single_records = [ResultValue::None;18];
-    let mut average_records = [ResultValue::None;18];
+    let mut average_sor_hashmap = single_sor_hashmap.clone();
 
-    for event in Event::iter() {
-        // Calculate ranks in event for single/average
-        let mut single_ranks = wa_cubers.iter()
-            .map(|c| RankRow { score: c.get_single(*event), rank: 0 as usize, data: [0 as usize; 0], person: c} )
-            .collect::<Vec<_>>();
-        let mut average_ranks = wa_cubers.iter()
-        .map(|c| RankRow { score: c.get_average(*event), rank: 0 as usize, data: [0 as usize; 0], person: c} )
-        .collect::<Vec<_>>();
-        rank(&mut single_ranks, true);
-        rank(&mut average_ranks, true);
-        
-        // Add records
-        average_records[*event as usize] = average_ranks[0].score;
-        single_records[*event as usize] = single_ranks[0].score;
-        
+    for ranks in event_ranks {
+        let event = ranks.event;
 
-        for row in single_ranks.iter() {
-            if single_ranks.last().unwrap().rank == row.rank {
-                single_sor_hashmap.get_mut(&row.person.id).unwrap().data[*event as usize] = SORRank::Default(row.rank);
+        for row in ranks.single.iter() {
+            if ranks.single.last().unwrap().rank == row.rank {
+                single_sor_hashmap.get_mut(&row.person.id).unwrap().data[event as usize] = SORRank::Default(row.rank);
             }
             else {
-                single_sor_hashmap.get_mut(&row.person.id).unwrap().data[*event as usize] = SORRank::Normal(row.rank);
+                single_sor_hashmap.get_mut(&row.person.id).unwrap().data[event as usize] = SORRank::Normal(row.rank);
             }
         }
 
-        for row in average_ranks.iter() {
-            if *event == Event::_3mbld {
-                average_sor_hashmap.get_mut(&row.person.id).unwrap().data[*event as usize] = SORRank::Blank;
+        for row in ranks.average.iter() {
+            if event == Event::_3mbld {
+                average_sor_hashmap.get_mut(&row.person.id).unwrap().data[event as usize] = SORRank::Blank;
             }
-            else if average_ranks.last().unwrap().rank == row.rank {
-                average_sor_hashmap.get_mut(&row.person.id).unwrap().data[*event as usize] = SORRank::Default(row.rank);
+            else if ranks.average.last().unwrap().rank == row.rank {
+                average_sor_hashmap.get_mut(&row.person.id).unwrap().data[event as usize] = SORRank::Default(row.rank);
             }
             else {
-                average_sor_hashmap.get_mut(&row.person.id).unwrap().data[*event as usize] = SORRank::Normal(row.rank);
+                average_sor_hashmap.get_mut(&row.person.id).unwrap().data[event as usize] = SORRank::Normal(row.rank);
             }
         }
-
-        let page = RankPage {
-            name:  event.to_string(), 
-            title: format!("WA {} Rankings", event.to_nice_str()), 
-            tables: vec![
-                RankTable { 
-                    label: "Single".to_string(), 
-                    rows: single_ranks.into_iter().filter(|r| r.score.valid()).collect::<Vec<_>>(),
-                    headers: ["";0]
-                },
-                RankTable { 
-                    label: "Average".to_string(), 
-                    rows: average_ranks.into_iter().filter(|r| r.score.valid()).collect::<Vec<_>>(),
-                    headers: ["";0]
-                }
-            ]
-        };
-
-        site.to_html_file(&page);
     }
 
     let mut single_sor = single_sor_hashmap
-        .into_iter()
-        .map(|(_, mut v)| {
+        .into_values()
+        .map(|mut v| {
             v.score = v.data.iter().map(|d| d.get_value()).sum();
             v
         })
@@ -757,35 +1814,22 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     rank(&mut single_sor, true);
 
     let mut average_sor = average_sor_hashmap
-    .into_iter()
-    .map(|(_, mut v)| {
-        v.score = v.data.iter().map(|d| d.get_value()).sum();
-        v
-    })
-    .collect::<Vec<_>>();
+        .into_values()
+        .map(|mut v| {
+            v.score = v.data.iter().map(|d| d.get_value()).sum();
+            v
+        })
+        .collect::<Vec<_>>();
     rank(&mut average_sor, true);
-    
-    let sor_page = RankPage {
-        name: "sor".to_string(),
-        title: "WA Sum Of Ranks".to_string(),
-        tables: vec![ 
-            RankTable {
-                label: "Single".to_string(),
-                rows: single_sor,
-                headers: Event::EVENT_DISPLAY_STRINGS
-            },
-            RankTable {
-                label: "Average".to_string(),
-                rows: average_sor,
-                headers: Event::EVENT_DISPLAY_STRINGS
-            }
-        ]
-    };
 
-    site.to_html_file(&sor_page);
+    (single_sor, average_sor)
+}
+
+fn kinch_ranks<'a>(cubers: &'a [Cuber], event_ranks: &[EventRanks<'a>]) -> Vec<RankRow<'a, f64, f64, 18>> {
+    let single_records = event_ranks.iter().map(|r| r.single_record()).collect::<Vec<_>>();
+    let average_records = event_ranks.iter().map(|r| r.average_record()).collect::<Vec<_>>();
 
-    // Calculate Kinch :
-    let mut kinch_rows = wa_cubers.iter()
+    let mut kinch_rows = cubers.iter()
         .map(|c| 
             {
                 let mut kinch_score: f64 = 0.0;
@@ -802,55 +1846,47 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     }
                     kinch_score += data[*e as usize];
                 }
-                RankRow { score: kinch_score/18.0, rank: 0 as usize, data: data, person: c} 
+                RankRow { score: kinch_score/18.0, rank: 0, data, person: c, movement: Movement::Unknown } 
             }
         )
         .collect::<Vec<_>>();
     rank(&mut kinch_rows, false);
-    
-    let kinch_page = RankPage {
-        name: "kinch".to_string(),
-        title: "WA Kinch Ranks".to_string(),
+
+    kinch_rows
+}
+
+fn sor_page<'a>(name: &str, title: &str, single_sor: Vec<SORRow<'a>>, average_sor: Vec<SORRow<'a>>, selector: Option<PageSelector>) -> RankPage<RankTable<'a, usize, SORRank, 18>> {
+    RankPage {
+        name: name.to_string(),
+        title: title.to_string(),
         tables: vec![ 
             RankTable {
-                label: "Ranks".to_string(),
-                rows: kinch_rows,
+                label: "Single".to_string(),
+                rows: single_sor,
+                headers: Event::EVENT_DISPLAY_STRINGS
+            },
+            RankTable {
+                label: "Average".to_string(),
+                rows: average_sor,
                 headers: Event::EVENT_DISPLAY_STRINGS
             }
-        ]
-    };
-
-    site.to_html_file(&kinch_page);
-        
-    site.gen_homepage();
-
-    Ok(())
-}
-
-#[derive(Clone, Copy)]
-enum SORRank {
-    Blank,
-    Normal(usize),
-    Default(usize)
-}
-
-impl SORRank {
-    pub fn get_value(&self) -> usize {
-        match self {
-            Self::Blank => 0,
-            Self::Normal(n) => *n,
-            Self::Default(n) => *n
-        }
+        ],
+        selector,
     }
 }
 
-impl ToHtml for SORRank {
-    fn to_html_string(&self) -> String {
-        match self {
-            SORRank::Blank => "<span> </span>".to_string(),
-            SORRank::Normal(r) => format!("<span> {} </span>", r),
-            SORRank::Default(r) => format!("<span style=\"color: var(--bs-orange);\"> {} </span>", r)
-        }
+fn kinch_page<'a>(name: &str, title: &str, kinch_rows: Vec<RankRow<'a, f64, f64, 18>>, selector: Option<PageSelector>) -> RankPage<RankTable<'a, f64, f64, 18>> {
+    RankPage {
+        name: name.to_string(),
+        title: title.to_string(),
+        tables: vec![ 
+            RankTable {
+                label: "Ranks".to_string(),
+                rows: kinch_rows,
+                headers: Event::EVENT_DISPLAY_STRINGS
+            }
+        ],
+        selector,
     }
 }
 
@@ -874,4 +1910,136 @@ fn rank<T, S, const N: usize>(rows: &mut Vec<RankRow<S, T, N>>, ascending: bool)
 
         rows[i].rank = rank;
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::comp_stats::StatsTable;
+    use crate::escape::HOSTILE;
+
+    fn date(s: &str) -> Date {
+        Date::parse(s).unwrap()
+    }
+
+    fn cuber(name: &str) -> Cuber {
+        Cuber { name: name.to_string(), id: "2016HOST01".to_string(), ..Default::default() }
+    }
+
+    fn stats_table(label: &str) -> StatsTable {
+        StatsTable { label: label.to_string(), headers: vec!["Competition"], rows: Vec::new() }
+    }
+
+    #[test]
+    fn tab_ids_are_readable() {
+        assert_eq!(tab_id("Single (last 12 months)"), "single-last-12-months");
+        assert_eq!(tab_id("Women's Average"), "women-s-average");
+        assert_eq!(tab_id("(!) -- ?"), "tab");
+        assert_eq!(tab_id(""), "tab");
+    }
+
+    #[test]
+    fn tab_ids_are_unique_on_a_page() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().to_str().unwrap();
+        std::fs::create_dir_all(format!("{root}/docs")).unwrap();
+
+        let mut site = Site::new("", root, "Test", Date { year: 2024, month: 1, day: 1 }, Assets::default());
+        site.to_unlisted_html_file(&mut RankPage {
+            name: "test".to_string(),
+            title: "Test".to_string(),
+            tables: vec![stats_table("Single"), stats_table("Single!"), stats_table("Single?")],
+            selector: None,
+        }).unwrap();
+        let html = std::fs::read_to_string(format!("{root}/docs/test.html")).unwrap();
+
+        for id in ["single", "single-2", "single-3"] {
+            assert_eq!(html.matches(&format!(r#"id="{id}-tab""#)).count(), 1, "{id}-tab in {html}");
+            assert_eq!(html.matches(&format!(r#"id="{id}-pane""#)).count(), 1, "{id}-pane in {html}");
+        }
+    }
+
+    #[test]
+    fn days_cross_leap_days_and_years() {
+        assert_eq!(date("2024-02-28").plus_days(1), date("2024-02-29"));
+        assert_eq!(date("2024-02-28").plus_days(2), date("2024-03-01"));
+        assert_eq!(date("2023-02-28").plus_days(1), date("2023-03-01"));
+        assert_eq!(date("2023-12-31").plus_days(1), date("2024-01-01"));
+        assert_eq!(date("1970-01-01").days_since_epoch(), 0);
+        assert_eq!(date("2000-03-01").days_since_epoch(), 11017);
+        assert_eq!(Date::from_days_since_epoch(19782), date("2024-02-29"));
+    }
+
+    #[test]
+    fn competitions_can_end_in_the_next_year() {
+        let comp = WCACompetition::test("2024", "12", "30", "1", "2");
+        assert_eq!(comp.start_date(), Some(date("2024-12-30")));
+        assert_eq!(comp.end_date(), Some(date("2025-01-02")));
+
+        let comp = WCACompetition::test("2024", "6", "1", "6", "2");
+        assert_eq!(comp.end_date(), Some(date("2024-06-02")));
+    }
+
+    #[test]
+    fn months_before_crosses_january() {
+        assert_eq!(date("2024-03-15").months_before(2), date("2024-01-15"));
+        assert_eq!(date("2024-01-15").months_before(1), date("2023-12-15"));
+        assert_eq!(date("2024-02-10").months_before(12), date("2023-02-10"));
+        assert_eq!(date("2024-02-10").months_before(14), date("2022-12-10"));
+    }
+
+    #[test]
+    fn parse_rejects_bad_dates() {
+        assert_eq!(Date::parse("2024-02-29"), Some(Date { year: 2024, month: 2, day: 29 }));
+        assert_eq!(Date::parse(" 2024-1-5"), Some(Date { year: 2024, month: 1, day: 5 }));
+        assert_eq!(Date::parse("2024-13-01"), None);
+        assert_eq!(Date::parse("2024-00-01"), None);
+        assert_eq!(Date::parse("2024-01-32"), None);
+        assert_eq!(Date::parse("2024-02-30"), None);
+        assert_eq!(Date::parse("2024-02-31"), None);
+        assert_eq!(Date::parse("2023-02-29"), None);
+        assert_eq!(Date::parse("2023-04-31"), None);
+        assert_eq!(Date::parse("2023-12-31"), Some(Date { year: 2023, month: 12, day: 31 }));
+        assert_eq!(Date::parse("2024-01-01-x"), None);
+        assert_eq!(Date::parse("2024-01"), None);
+        assert_eq!(Date::parse("today"), None);
+    }
+
+    #[test]
+    fn rank_table_escapes_names_and_labels() {
+        for name in HOSTILE {
+            let person = cuber(name);
+            let table = RankTable::<ResultValue, ResultValue, 0> {
+                label: name.to_string(),
+                rows: vec![RankRow { rank: 1, score: ResultValue::Time(1000), data: [], person: &person, movement: Movement::Unknown }],
+                headers: [],
+            };
+            let html = table.try_to_html_string().unwrap();
+
+            assert!(!html.contains(name), "{name} was left unescaped in {html}");
+            assert!(!html.contains("<script") && !html.contains("<b>"));
+        }
+    }
+
+    #[test]
+    fn ids_are_safe_for_selectors() {
+        for name in HOSTILE {
+            let table = RankTable::<ResultValue, ResultValue, 0> { label: name.to_string(), rows: Vec::new(), headers: [] };
+            assert!(tab_id(&table.get_label()).chars().all(|c| c.is_ascii_alphanumeric() || c == '-'));
+        }
+    }
+
+    #[test]
+    fn selector_escapes_text_and_links() {
+        let selector = PageSelector {
+            label: "<i>Year</i>".to_string(),
+            pages: HOSTILE.iter().map(|n| (n.to_string(), n.to_string())).collect(),
+        };
+        let html = selector.to_html_string(HOSTILE[0]);
+
+        assert!(!html.contains("<i>") && !html.contains("<script") && !html.contains("<b>"));
+        // Page names end up in hrefs, so a quote mustn't be able to end the attribute
+        for name in HOSTILE {
+            assert!(html.contains(&format!(r#"href="{}.html""#, escape::attr(name))));
+        }
+    }
+}
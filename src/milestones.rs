@@ -0,0 +1,129 @@
+use rustc_hash::FxHashMap;
+use crate::{Cuber, Date, escape, Event, JsonCell, Milestone, RankRow, RankTable, ResultValue, ToHtml, html_id, rank};
+use crate::comp_stats::{StatsRow, StatsTable};
+use crate::movement::Movement;
+
+#[derive(Clone)]
+pub enum MilestoneCell {
+    Result(ResultValue),
+    Competition(String),
+}
+
+impl ToHtml for MilestoneCell {
+    fn to_html_string(&self) -> String {
+        match self {
+            MilestoneCell::Result(r) => r.to_html_string(),
+            MilestoneCell::Competition(c) => escape::text(c),
+        }
+    }
+}
+
+impl JsonCell for MilestoneCell {
+    fn raw(&self) -> serde_json::Value {
+        match self {
+            MilestoneCell::Result(r) => r.raw(),
+            MilestoneCell::Competition(c) => serde_json::json!(c),
+        }
+    }
+
+    fn formatted(&self) -> String {
+        match self {
+            MilestoneCell::Result(r) => r.formatted(),
+            MilestoneCell::Competition(c) => c.clone(),
+        }
+    }
+
+    fn raw_csv(&self) -> Option<String> {
+        match self {
+            MilestoneCell::Result(r) => r.raw_csv(),
+            MilestoneCell::Competition(_) => None,
+        }
+    }
+}
+
+pub fn page_name(milestone: &Milestone) -> String {
+    format!("milestone_{}", html_id(milestone.label).to_lowercase())
+}
+
+// When (and with what result) the person first got under the milestone
+fn first_achieved(cuber: &Cuber, milestone: &Milestone, comp_dates: &FxHashMap<String, Date>) -> Option<(Date, ResultValue, String)> {
+    cuber.results
+        .iter()
+        .filter(|r| Event::str_to_index(&r.eventId) == milestone.event as usize)
+        .filter_map(|r| {
+            let result = if milestone.average { r.get_average() } else { r.get_single() };
+            let date = comp_dates.get(&r.competitionId)?;
+            (result.valid() && result < milestone.under).then(|| (*date, result, r.competitionId.clone()))
+        })
+        .min_by(|a, b| a.0.cmp(&b.0).then(a.1.cmp(&b.1)))
+}
+
+// Everyone who's achieved the milestone, ranked by the order they got there
+pub fn first_achievements<'a>(cubers: &'a [Cuber], milestone: &Milestone, comp_dates: &FxHashMap<String, Date>, comp_names: &FxHashMap<String, String>) -> RankTable<'a, Date, MilestoneCell, 2> {
+    let mut rows = cubers
+        .iter()
+        .filter_map(|c| {
+            let (date, result, comp) = first_achieved(c, milestone, comp_dates)?;
+            let comp_name = comp_names.get(&comp).cloned().unwrap_or(comp);
+            Some(RankRow { 
+                rank: 0, 
+                score: date, 
+                data: [MilestoneCell::Result(result), MilestoneCell::Competition(comp_name)], 
+                person: c, 
+                movement: Movement::Unknown 
+            })
+        })
+        .collect::<Vec<_>>();
+    rank(&mut rows, true);
+
+    RankTable {
+        label: "Order Reached".to_string(),
+        rows,
+        headers: ["Achieved With", "Competition"],
+    }
+}
+
+// Running total of achievers for every milestone at the end of each year
+pub fn achievers_over_time(cubers: &[Cuber], comp_dates: &FxHashMap<String, Date>) -> StatsTable {
+    let achieved = Event::MILESTONES
+        .iter()
+        .map(|m| cubers.iter().filter_map(|c| first_achieved(c, m, comp_dates).map(|a| a.0.year)).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+
+    let first_year = achieved.iter().flatten().min().copied();
+    let last_year = cubers
+        .iter()
+        .flat_map(|c| c.competition_dates(comp_dates))
+        .map(|d| d.year)
+        .max();
+
+    let rows = match (first_year, last_year) {
+        (Some(first), Some(last)) => (first..=last)
+            .rev()
+            .map(|year| StatsRow {
+                cells: std::iter::once(year.to_string())
+                    .chain(achieved.iter().map(|years| years.iter().filter(|y| **y <= year).count().to_string()))
+                    .collect(),
+                bar: None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    StatsTable {
+        label: "Achievers Over Time".to_string(),
+        headers: std::iter::once("Year").chain(Event::MILESTONES.iter().map(|m| m.label)).collect(),
+        rows,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn competition_names_are_escaped() {
+        let cell = MilestoneCell::Competition("Cubing <At> The \"Beach\" & Co".to_string());
+        assert_eq!(cell.to_html_string(), "Cubing &lt;At&gt; The \"Beach\" &amp; Co");
+    }
+}
@@ -0,0 +1,101 @@
+use std::collections::BTreeMap;
+use rustc_hash::FxHashMap;
+use crate::{JsonCell, RankPage, PageItem, ToHtml};
+
+// How someone's rank changed since the previous build
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub enum Movement {
+    #[default]
+    Unknown, // No previous build to compare against
+    New,
+    Same,
+    Up(usize),
+    Down(usize),
+}
+
+impl Movement {
+    pub fn between(previous: Option<usize>, current: usize) -> Self {
+        match previous {
+            None                    => Movement::New,
+            Some(p) if p > current  => Movement::Up(p - current),
+            Some(p) if p < current  => Movement::Down(current - p),
+            Some(_)                 => Movement::Same,
+        }
+    }
+}
+
+impl ToHtml for Movement {
+    fn to_html_string(&self) -> String {
+        match self {
+            Movement::Unknown | Movement::Same => "".to_string(),
+            Movement::New => r#"<span class="badge text-bg-info ms-1">new</span>"#.to_string(),
+            Movement::Up(d) => format!(r#"<span class="text-success ms-1"><i class="bi bi-arrow-up"></i>{d}</span>"#),
+            Movement::Down(d) => format!(r#"<span class="text-danger ms-1"><i class="bi bi-arrow-down"></i>{d}</span>"#),
+        }
+    }
+}
+
+// Places moved up (positive) or down (negative), null without a previous build
+impl JsonCell for Movement {
+    fn raw(&self) -> serde_json::Value {
+        match self {
+            Movement::Unknown => serde_json::Value::Null,
+            Movement::New => serde_json::json!("new"),
+            Movement::Same => serde_json::json!(0),
+            Movement::Up(d) => serde_json::json!(*d as isize),
+            Movement::Down(d) => serde_json::json!(-(*d as isize)),
+        }
+    }
+}
+
+// Page items whose rows can be compared against the ranks from a previous build.
+// Items without ranks (stats, charts) keep the defaults, so they have no movement and stay out of the snapshot.
+pub trait Ranked {
+    fn ranks(&self) -> Vec<(String, usize)> { // (person id, rank)
+        Vec::new()
+    }
+
+    fn set_movements(&mut self, _previous: &FxHashMap<String, usize>) {}
+}
+
+// Ranks from every table in the last build, keyed by "page/table" then person id.
+// BTreeMaps so the saved file diffs nicely in git.
+type Snapshot = BTreeMap<String, BTreeMap<String, usize>>;
+
+pub struct RankHistory {
+    previous: Snapshot,
+    current: Snapshot,
+}
+
+impl RankHistory {
+    pub fn load(path: &str) -> Self {
+        let previous = std::fs::read_to_string(path)
+            .ok()
+            .and_then(|s| serde_json::from_str::<Snapshot>(&s).ok())
+            .unwrap_or_default();
+
+        RankHistory { previous, current: Snapshot::new() }
+    }
+
+    // Records the page's ranks and marks every row with how it moved since the previous snapshot
+    pub fn diff<T>(&mut self, page: &mut RankPage<T>) where T: PageItem {
+        for table in page.tables.iter_mut() {
+            let key = format!("{}/{}", page.name, table.get_label());
+
+            if let Some(previous) = self.previous.get(&key) {
+                let previous = FxHashMap::from_iter(previous.iter().map(|(id, r)| (id.clone(), *r)));
+                table.set_movements(&previous);
+            }
+
+            // Tabs without ranks (charts, stats) would only bloat the snapshot
+            let ranks = table.ranks();
+            if !ranks.is_empty() {
+                self.current.insert(key, ranks.into_iter().collect());
+            }
+        }
+    }
+
+    pub fn save(&self, path: &str) -> std::io::Result<()> {
+        std::fs::write(path, serde_json::to_string(&self.current)?)
+    }
+}
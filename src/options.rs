@@ -0,0 +1,102 @@
+use crate::Date;
+use crate::assets::Assets;
+use crate::ratings::EloParams;
+
+// Command line options, e.g. `cargo run --release -- --as-of 2018-12-31`
+pub struct Options {
+    // Build the site as it would have looked on this date (ignoring every later competition)
+    pub as_of: Option<Date>,
+    // Size of the rolling window used for the recent form rankings
+    pub recent_months: usize,
+    // Women's rankings for each event, SOR and Kinch
+    pub womens_rankings: bool,
+    // Fewest official attempts needed to appear on the consistency and success rate leaderboards
+    pub min_attempts: usize,
+    pub elo: EloParams,
+    // Only build a psych sheet for the competition in this WCIF file
+    pub psych_sheet: Option<String>,
+    // Only build podium predictions for the competitions in these WCIF files (--predict can be repeated)
+    pub predict: Vec<String>,
+    // WCIF files or WCA Live dumps with results from comps the export doesn't have yet (--provisional can be repeated)
+    pub provisional: Vec<String>,
+    // Directory of templates to use instead of the built-in ones (any that are missing fall back to the built-in one)
+    pub templates: Option<String>,
+    // Where pages load Bootstrap from: its CDN, a copy bundled into docs/assets, or a minimal built-in stylesheet
+    pub assets: Assets,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            as_of: None,
+            recent_months: 12,
+            womens_rankings: true,
+            min_attempts: 20,
+            elo: EloParams::default(),
+            psych_sheet: None,
+            predict: Vec::new(),
+            provisional: Vec::new(),
+            templates: None,
+            assets: Assets::default(),
+        }
+    }
+}
+
+impl Options {
+    pub fn from_args<I>(mut args: I) -> Result<Self, String> where I: Iterator<Item = String> {
+        let mut options = Options::default();
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--as-of" => {
+                    let value = args.next().ok_or("--as-of needs a date (YYYY-MM-DD)")?;
+                    options.as_of = Some(Date::parse(&value).ok_or(format!("Invalid date for --as-of: {value}"))?);
+                },
+                "--recent-months" => {
+                    let value = args.next().ok_or("--recent-months needs a number of months")?;
+                    options.recent_months = value.parse().ok().filter(|m| *m > 0).ok_or(format!("Invalid number of months for --recent-months: {value}"))?;
+                },
+                "--min-attempts" => {
+                    let value = args.next().ok_or("--min-attempts needs a number of attempts")?;
+                    options.min_attempts = value.parse().map_err(|_| format!("Invalid number of attempts for --min-attempts: {value}"))?;
+                },
+                "--elo-k" => {
+                    let value = args.next().ok_or("--elo-k needs a number")?;
+                    options.elo.k = value.parse().ok().filter(|k: &f64| *k > 0.0).ok_or(format!("Invalid K factor for --elo-k: {value}"))?;
+                },
+                "--elo-initial" => {
+                    let value = args.next().ok_or("--elo-initial needs a rating")?;
+                    options.elo.initial = value.parse().map_err(|_| format!("Invalid rating for --elo-initial: {value}"))?;
+                },
+                "--psych-sheet" => {
+                    options.psych_sheet = Some(args.next().ok_or("--psych-sheet needs a WCIF file")?);
+                },
+                "--predict" => {
+                    options.predict.push(args.next().ok_or("--predict needs a WCIF file")?);
+                },
+                "--provisional" => {
+                    options.provisional.push(args.next().ok_or("--provisional needs a WCIF or WCA Live results file")?);
+                },
+                "--templates" => {
+                    options.templates = Some(args.next().ok_or("--templates needs a directory")?);
+                },
+                "--assets" => {
+                    let value = args.next().ok_or("--assets needs cdn, bundle or minimal")?;
+                    options.assets = Assets::parse(&value).ok_or(format!("Invalid value for --assets (cdn, bundle or minimal): {value}"))?;
+                },
+                "--no-womens-rankings" => options.womens_rankings = false,
+                _ => return Err(format!("Unknown argument: {arg}")),
+            }
+        }
+
+        Ok(options)
+    }
+
+    // Where index.html and docs/ are written
+    pub fn output_root(&self) -> String {
+        match self.as_of {
+            Some(date) => format!("as_of/{date}"),
+            None => ".".to_string(),
+        }
+    }
+}
@@ -0,0 +1,313 @@
+use rustc_hash::FxHashMap;
+use crate::{Cuber, Date, Event, PageItem, RankPage, RankRow, RankTable, ResultValue, rank};
+use crate::comp_stats::{StatsRow, StatsTable};
+use crate::consistency::Stat;
+use crate::movement::Movement;
+use crate::wcif::{Wcif, WcifAdvancement, WcifRound};
+
+const SIMULATIONS: usize = 10_000;
+// Below this many recent attempts, everything before the comp is sampled instead
+const MIN_RECENT_ATTEMPTS: usize = 10;
+
+// xorshift64*, seeded the same every run so the predictions don't change between builds
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545F4914F6CDD1D)
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+}
+
+// Smaller is better, None is a DNF. Multi is (-points, seconds) squashed into one number.
+fn sort_key(value: ResultValue) -> Option<f64> {
+    match value {
+        ResultValue::Time(v) | ResultValue::Moves(v) => Some(v as f64),
+        ResultValue::Multi { time, solved, attempted } if 2 * solved >= attempted => Some(((attempted - 2 * solved) * 100_000 + time) as f64),
+        _ => None,
+    }
+}
+
+fn attempts_in_format(format: &str) -> usize {
+    match format {
+        "1" => 1,
+        "2" => 2,
+        "3" | "m" => 3,
+        _ => 5,
+    }
+}
+
+// (result the round is ranked by, best single) for one set of attempts, in the units the WCIF uses
+fn round_result(event: Event, format: &str, attempts: &[Option<f64>]) -> (Option<f64>, Option<f64>) {
+    let best = attempts.iter().flatten().copied().reduce(f64::min);
+    let scale = if event == Event::_3fm { 100.0 } else { 1.0 }; // FM means are stored as moves * 100
+
+    let primary = match format {
+        "a" => {
+            let mut sorted = attempts.iter().map(|a| a.unwrap_or(f64::INFINITY)).collect::<Vec<_>>();
+            sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            let middle = &sorted[1..sorted.len() - 1];
+            middle.iter().all(|a| a.is_finite()).then(|| scale * middle.iter().sum::<f64>() / middle.len() as f64)
+        },
+        "m" => attempts.iter().all(|a| a.is_some()).then(|| scale * attempts.iter().flatten().sum::<f64>() / attempts.len() as f64),
+        _ => best,
+    };
+
+    (primary, best)
+}
+
+// How many of the ranked competitors go through to the next round
+fn advancing(condition: &WcifAdvancement, results: &[Option<f64>]) -> usize {
+    let competed = results.len();
+    let count = match condition.kind.as_str() {
+        "ranking" => condition.level.max(0) as usize,
+        "percent" => competed * condition.level.max(0) as usize / 100,
+        "attemptResult" => results.iter().filter(|r| r.is_some_and(|r| r < condition.level as f64)).count(),
+        _ => competed,
+    };
+    // Regulation 9p1: at most 75% of competitors advance
+    count.min(competed * 3 / 4)
+}
+
+struct Competitor<'a> {
+    cuber: &'a Cuber,
+    attempts: Vec<Option<f64>>, // Pool of past attempts to sample from
+    actual: Option<usize>,      // Place in the final, if the comp is already in the export
+}
+
+struct Chances {
+    win: usize,
+    podium: usize,
+    finals: usize,
+}
+
+// Plays every round of the event SIMULATIONS times, sampling each attempt from the competitor's past attempts
+fn simulate(event: Event, rounds: &[WcifRound], competitors: &[Competitor], rng: &mut Rng) -> Vec<Chances> {
+    let mut chances = competitors.iter().map(|_| Chances { win: 0, podium: 0, finals: 0 }).collect::<Vec<_>>();
+
+    for _ in 0..SIMULATIONS {
+        let mut in_round = (0..competitors.len()).collect::<Vec<_>>();
+
+        for (i, round) in rounds.iter().enumerate() {
+            let n = attempts_in_format(&round.format);
+            let mut results = in_round.iter().map(|&c| {
+                let pool = &competitors[c].attempts;
+                let attempts = (0..n)
+                    .map(|_| if pool.is_empty() { None } else { pool[rng.below(pool.len())] })
+                    .collect::<Vec<_>>();
+                let (primary, best) = round_result(event, &round.format, &attempts);
+                (c, primary, best)
+            }).collect::<Vec<_>>();
+            results.sort_by(|a, b| {
+                let key = |r: &(usize, Option<f64>, Option<f64>)| (r.1.unwrap_or(f64::INFINITY), r.2.unwrap_or(f64::INFINITY));
+                key(a).partial_cmp(&key(b)).unwrap()
+            });
+
+            let last = i + 1 == rounds.len();
+            match round.advancement_condition.as_ref() {
+                Some(condition) if !last => {
+                    let primaries = results.iter().map(|r| r.1).collect::<Vec<_>>();
+                    in_round = results.iter().take(advancing(condition, &primaries)).map(|r| r.0).collect();
+                },
+                _ => {
+                    for (place, (c, _, best)) in results.iter().enumerate() {
+                        chances[*c].finals += 1;
+                        if best.is_some() && place < 3 {
+                            chances[*c].podium += 1;
+                            if place == 0 {
+                                chances[*c].win += 1;
+                            }
+                        }
+                    }
+                    break;
+                },
+            }
+        }
+    }
+
+    chances
+}
+
+fn percent(count: usize) -> Stat {
+    Stat::Percent(100.0 * count as f64 / SIMULATIONS as f64)
+}
+
+// Where the person actually finished in the final, from the export
+fn actual_place(cuber: &Cuber, comp_id: &str, event: Event) -> Option<usize> {
+    cuber.results
+        .iter()
+        .find(|r| r.competitionId == comp_id
+            && Event::str_to_index(&r.eventId) == event as usize
+            && (r.roundTypeId == "f" || r.roundTypeId == "c")
+            && r.get_single().valid())
+        .and_then(|r| r.pos.parse().ok())
+}
+
+// (predicted podium chance, actually podiumed) for everyone in events that have already happened
+pub type Outcomes = Vec<(f64, bool)>;
+
+pub fn predictions_page<'a>(wcif: &Wcif, registrants: &'a [(Cuber, Vec<String>)], comp_dates: &FxHashMap<String, Date>, recent_months: usize) -> (RankPage<Box<dyn PageItem + 'a>>, Outcomes) {
+    // Only results from before the comp are used, so a past comp's WCIF gives an honest backtest
+    let start = wcif.start_date().unwrap_or_else(Date::today);
+    let recent_cutoff = start.months_before(recent_months);
+    let mut rng = Rng(0x9E3779B97F4A7C15);
+    let mut outcomes = Outcomes::new();
+
+    let mut tables = wcif.events
+        .iter()
+        .filter_map(|e| Event::from_id(&e.id).map(|event| (e, event)))
+        .filter(|(e, _)| !e.rounds.is_empty())
+        .map(|(wcif_event, event)| {
+            let competitors = registrants
+                .iter()
+                .filter(|(_, events)| events.contains(&wcif_event.id))
+                .map(|(c, _)| {
+                    let dated = c.results
+                        .iter()
+                        .filter(|r| Event::str_to_index(&r.eventId) == event as usize)
+                        .filter_map(|r| comp_dates.get(&r.competitionId).filter(|d| **d < start).map(|d| (r, *d)))
+                        .collect::<Vec<_>>();
+                    let sample = |after: Date| dated
+                        .iter()
+                        .filter(|(_, d)| *d > after)
+                        .flat_map(|(r, _)| r.attempts())
+                        .filter(|a| !matches!(a, ResultValue::DNS))
+                        .map(sort_key)
+                        .collect::<Vec<_>>();
+
+                    let recent = sample(recent_cutoff);
+                    Competitor {
+                        cuber: c,
+                        attempts: if recent.len() >= MIN_RECENT_ATTEMPTS { recent } else { sample(Date { year: 0, month: 1, day: 1 }) },
+                        actual: actual_place(c, &wcif.id, event),
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            let chances = simulate(event, &wcif_event.rounds, &competitors, &mut rng);
+
+            if competitors.iter().any(|c| c.actual.is_some()) {
+                outcomes.extend(competitors.iter().zip(&chances).map(|(c, ch)| {
+                    (ch.podium as f64 / SIMULATIONS as f64, c.actual.is_some_and(|p| p <= 3))
+                }));
+            }
+
+            let mut rows = competitors.iter().zip(&chances).map(|(c, ch)| RankRow {
+                rank: 0,
+                score: percent(ch.podium),
+                data: [percent(ch.win), percent(ch.finals), c.actual.map_or(Stat::Blank, Stat::Count)],
+                person: c.cuber,
+                movement: Movement::Unknown,
+            }).collect::<Vec<_>>();
+            // Stable sort, so people with the same podium chance stay ordered by their chance to win
+            rows.sort_by(|a, b| b.data[0].partial_cmp(&a.data[0]).unwrap());
+            rank(&mut rows, false);
+
+            Box::new(RankTable {
+                label: event.to_nice_str().to_string(),
+                rows,
+                headers: ["Win", "Make Final", "Actual Place"],
+            }) as Box<dyn PageItem>
+        })
+        .collect::<Vec<_>>();
+
+    if !outcomes.is_empty() {
+        tables.push(Box::new(calibration_table(&outcomes)));
+    }
+
+    let page = RankPage {
+        name: format!("predictions_{}", wcif.id),
+        title: format!("{} Podium Predictions", wcif.name),
+        tables,
+        selector: None,
+    };
+
+    (page, outcomes)
+}
+
+// How often predicted podiums actually happened, bucketed by the predicted chance.
+// A well calibrated model has the actual rate close to the average prediction in every bucket.
+pub fn calibration_table(outcomes: &Outcomes) -> StatsTable {
+    let buckets: [(f64, f64); 5] = [(0.0, 0.05), (0.05, 0.25), (0.25, 0.5), (0.5, 0.75), (0.75, 1.01)];
+
+    let row = |label: String, within: Vec<&(f64, bool)>| {
+        let count = within.len().max(1) as f64;
+        let podiums = within.iter().filter(|(_, o)| *o).count();
+        let brier = within.iter().map(|(p, o)| (p - if *o { 1.0 } else { 0.0 }).powi(2)).sum::<f64>() / count;
+        StatsRow {
+            cells: vec![
+                label,
+                within.len().to_string(),
+                format!("{:.1}%", 100.0 * within.iter().map(|(p, _)| p).sum::<f64>() / count),
+                podiums.to_string(),
+                format!("{:.1}%", 100.0 * podiums as f64 / count),
+                format!("{brier:.4}"),
+            ],
+            bar: None,
+        }
+    };
+
+    let mut rows = buckets
+        .iter()
+        .map(|&(low, high)| (low, high, outcomes.iter().filter(|(p, _)| *p >= low && *p < high).collect::<Vec<_>>()))
+        .filter(|(_, _, within)| !within.is_empty())
+        .map(|(low, high, within)| row(format!("{:.0}% to {:.0}%", 100.0 * low, 100.0 * high.min(1.0)), within))
+        .collect::<Vec<_>>();
+    rows.push(row("All".to_string(), outcomes.iter().collect()));
+
+    StatsTable {
+        label: "Calibration".to_string(),
+        headers: vec!["Predicted Chance", "Predictions", "Average Prediction", "Podiums", "Actual Rate", "Brier Score"],
+        rows,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn condition(kind: &str, level: isize) -> WcifAdvancement {
+        WcifAdvancement { kind: kind.to_string(), level }
+    }
+
+    #[test]
+    fn average_of_5_drops_one_dnf_as_the_worst() {
+        let (average, best) = round_result(Event::_3, "a", &[Some(1000.0), Some(1200.0), None, Some(1100.0), Some(1300.0)]);
+        assert_eq!(average, Some(1200.0));
+        assert_eq!(best, Some(1000.0));
+    }
+
+    #[test]
+    fn average_of_5_with_two_dnfs_is_a_dnf() {
+        let (average, best) = round_result(Event::_3, "a", &[Some(1000.0), None, Some(1100.0), None, Some(1300.0)]);
+        assert_eq!(average, None);
+        assert_eq!(best, Some(1000.0));
+    }
+
+    #[test]
+    fn fm_means_are_scaled_by_100() {
+        let (mean, best) = round_result(Event::_3fm, "m", &[Some(30.0), Some(32.0), Some(34.0)]);
+        assert_eq!(mean, Some(3200.0));
+        assert_eq!(best, Some(30.0));
+
+        let (mean, _) = round_result(Event::_3fm, "m", &[Some(30.0), None, Some(34.0)]);
+        assert_eq!(mean, None);
+    }
+
+    #[test]
+    fn at_most_75_percent_advance() {
+        let results = vec![Some(1000.0); 8];
+        assert_eq!(advancing(&condition("ranking", 4), &results), 4);
+        assert_eq!(advancing(&condition("ranking", 8), &results), 6);
+        assert_eq!(advancing(&condition("percent", 50), &results), 4);
+        assert_eq!(advancing(&condition("percent", 100), &results), 6);
+        assert_eq!(advancing(&condition("attemptResult", 1500), &results), 6);
+        assert_eq!(advancing(&condition("attemptResult", 500), &results), 0);
+    }
+}
@@ -0,0 +1,269 @@
+use rustc_hash::FxHashMap;
+use crate::{Cuber, Date, escape, Event, JsonCell, Labelled, PageItem, RankPage, Ranked, ResultValue, ToCsv, ToHtml, ToJson};
+use crate::head_to_head;
+use crate::ratings::round_order;
+use crate::svg::Frame;
+
+const FRAME: Frame = Frame { height: 220.0, left: 60.0, top: 20.0 }; // The top is room for the legend
+
+// One round's results, in the order the person competed
+pub struct Point {
+    date: Date,
+    competition: String,
+    single: ResultValue,
+    average: ResultValue,
+}
+
+pub fn profile_page_name(id: &str) -> String {
+    format!("person_{id}")
+}
+
+pub fn points(cuber: &Cuber, event: Event, comp_dates: &FxHashMap<String, Date>, comp_names: &FxHashMap<String, String>) -> Vec<Point> {
+    let mut rounds = cuber.results
+        .iter()
+        .filter(|r| Event::str_to_index(&r.eventId) == event as usize)
+        .filter_map(|r| comp_dates.get(&r.competitionId).map(|d| (*d, round_order(&r.roundTypeId), r)))
+        .collect::<Vec<_>>();
+    rounds.sort_by_key(|(date, round, _)| (*date, *round));
+
+    rounds
+        .into_iter()
+        .map(|(date, _, r)| Point {
+            date,
+            competition: comp_names.get(&r.competitionId).cloned().unwrap_or_default(),
+            single: r.get_single(),
+            average: r.get_average(),
+        })
+        .collect()
+}
+
+// Where a result goes on the y axis. Everything is in hundredths (FM singles are whole moves) except multi,
+// which is plotted by points. Returns None for DNFs.
+pub fn plot_value(value: ResultValue, event: Event, average: bool) -> Option<f64> {
+    if !value.valid() {
+        return None;
+    }
+    match value {
+        ResultValue::Time(t) => Some(t as f64),
+        ResultValue::Moves(m) if event == Event::_3fm && !average => Some(100.0 * m as f64),
+        ResultValue::Moves(m) => Some(m as f64),
+        ResultValue::Multi { solved, attempted, .. } => Some((2 * solved - attempted) as f64),
+        _ => None,
+    }
+}
+
+pub fn axis_label(value: f64, event: Event) -> String {
+    match event {
+        Event::_3mbld => format!("{value:.0} pts"),
+        Event::_3fm => format!("{:.1}", value / 100.0),
+        _ => ResultValue::Time(value.round() as isize).to_string(),
+    }
+}
+
+// Inline SVG of every single and average, with a step line for each PB
+pub fn chart_svg(points: &[Point], event: Event) -> String {
+    let higher_is_better = event == Event::_3mbld;
+    let series = [false, true].map(|average| {
+        points
+            .iter()
+            .filter_map(|p| {
+                let value = if average { p.average } else { p.single };
+                plot_value(value, event, average).map(|v| (p, value, v))
+            })
+            .collect::<Vec<_>>()
+    });
+
+    let days = series.iter().flatten().map(|(p, _, _)| p.date.days_since_epoch() as f64).collect::<Vec<_>>();
+    let values = series.iter().flatten().map(|(_, _, v)| *v).collect::<Vec<_>>();
+    if values.is_empty() {
+        return String::new();
+    }
+
+    // Pad the ranges so a single point (or a flat line) still has somewhere to go
+    let (mut x_min, mut x_max) = (days.iter().copied().fold(f64::MAX, f64::min), days.iter().copied().fold(f64::MIN, f64::max));
+    if x_max - x_min < 60.0 {
+        x_min -= 30.0;
+        x_max += 30.0;
+    }
+    let (mut y_min, mut y_max) = (values.iter().copied().fold(f64::MAX, f64::min), values.iter().copied().fold(f64::MIN, f64::max));
+    let pad = ((y_max - y_min) * 0.05).max(1.0);
+    y_min -= pad;
+    y_max += pad;
+
+    let x = |day: f64| FRAME.x(day, x_min, x_max);
+    let y = |value: f64| FRAME.y(value, y_min, y_max);
+
+    let mut svg = FRAME.open(&format!("{} progression", event.to_nice_str()));
+
+    // Axis labels
+    svg += &FRAME.y_labels((0..=3).map(|i| {
+        let value = y_min + (y_max - y_min) * i as f64 / 3.0;
+        (y(value), axis_label(value, event))
+    }));
+    let (first_year, last_year) = (points.first().map_or(0, |p| p.date.year), points.last().map_or(0, |p| p.date.year) + 1);
+    let step = (last_year - first_year).div_ceil(8).max(1);
+    svg += &FRAME.x_labels((first_year..=last_year)
+        .step_by(step)
+        .map(|year| (year, Date { year, month: 1, day: 1 }.days_since_epoch() as f64))
+        .filter(|(_, day)| (x_min..=x_max).contains(day))
+        .map(|(year, day)| (x(day), year.to_string())));
+
+    // Legend
+    svg += &format!(
+        r#"<circle cx="{}" cy="8" r="3" style="fill: var(--bs-info);"/><text x="{}" y="8" dominant-baseline="middle" fill="currentColor">Single</text>"#,
+        FRAME.left + 5.0, FRAME.left + 12.0
+    );
+    svg += &format!(
+        r#"<rect x="{}" y="5" width="6" height="6" style="fill: var(--bs-warning);"/><text x="{}" y="8" dominant-baseline="middle" fill="currentColor">Average</text>"#,
+        FRAME.left + 62.0, FRAME.left + 72.0
+    );
+
+    for (average, results) in series.iter().enumerate() {
+        let colour = if average == 1 { "var(--bs-warning)" } else { "var(--bs-info)" };
+
+        // The PB line steps to each new PB and carries on to the last result
+        let mut best: Option<f64> = None;
+        let mut path = String::new();
+        for (p, _, value) in results.iter() {
+            let px = x(p.date.days_since_epoch() as f64);
+            let improved = best.is_none_or(|b| if higher_is_better { *value > b } else { *value < b });
+            if improved {
+                path += &match best {
+                    None => format!("M {px:.1} {:.1}", y(*value)),
+                    Some(_) => format!(" H {px:.1} V {:.1}", y(*value)),
+                };
+                best = Some(*value);
+            }
+        }
+        if let Some((last, _, _)) = results.last() {
+            path += &format!(" H {:.1}", x(last.date.days_since_epoch() as f64));
+            svg += &format!(r#"<path d="{path}" fill="none" style="stroke: {colour};" stroke-width="1.5"/>"#);
+        }
+
+        for (p, result, value) in results.iter() {
+            let (px, py) = (x(p.date.days_since_epoch() as f64), y(*value));
+            let title = format!("<title>{}: {} ({})</title>", p.date, escape::text(&result.to_string()), escape::text(&p.competition));
+            svg += &if average == 1 {
+                format!(r#"<rect x="{:.1}" y="{:.1}" width="6" height="6" style="fill: {colour};" fill-opacity="0.8">{title}</rect>"#, px - 3.0, py - 3.0)
+            }
+            else {
+                format!(r#"<circle cx="{px:.1}" cy="{py:.1}" r="3" style="fill: {colour};" fill-opacity="0.8">{title}</circle>"#)
+            };
+        }
+    }
+
+    svg + "</svg>"
+}
+
+// A titled chart, optionally linking to the person's profile
+pub struct Chart {
+    pub title: String,
+    pub link: Option<String>,
+    pub points: Vec<Point>,
+}
+
+// A tab of progression charts for one event
+pub struct ChartTab {
+    pub label: String,
+    pub event: Event,
+    pub charts: Vec<Chart>,
+    pub compare: Option<String>, // Person to link to head to head records for
+}
+
+impl Labelled for ChartTab {
+    fn get_label(&self) -> String {
+        self.label.to_string()
+    }
+}
+
+impl Ranked for ChartTab {}
+
+// The points are in the page's JSON, so there's nothing for a spreadsheet
+impl ToCsv for ChartTab {}
+
+impl PageItem for ChartTab {}
+
+impl ToJson for ChartTab {
+    fn to_json(&self) -> serde_json::Value {
+        let charts = self.charts.iter().map(|c| serde_json::json!({
+            "title": c.title,
+            "points": c.points.iter().map(|p| serde_json::json!({
+                "date": p.date.to_string(),
+                "competition": p.competition,
+                "single": p.single.cell_json(),
+                "average": p.average.cell_json(),
+            })).collect::<Vec<_>>(),
+        })).collect::<Vec<_>>();
+
+        serde_json::json!({ "label": self.label, "charts": charts })
+    }
+}
+
+impl ToHtml for ChartTab {
+    fn to_html_string(&self) -> String {
+        let charts = self.charts.iter().map(|c| {
+            let title = match &c.link {
+                Some(link) => format!(r#"<a href="{}" class="link-body-emphasis">{}</a>"#, escape::attr(link), escape::text(&c.title)),
+                None => escape::text(&c.title),
+            };
+            format!(r#"
+                <div class="col-lg-6">
+                    <h5 class="mt-3"> {title} </h5>
+                    {}
+                </div>
+            "#, chart_svg(&c.points, self.event))
+        }).collect::<Vec<_>>().join("\n");
+
+        let compare = self.compare.as_ref().map(|id| format!(
+            r#"<a href="{}" class="btn btn-sm btn-outline-secondary my-2">Head to head</a>"#,
+            escape::attr(&head_to_head::page_link(id))
+        )).unwrap_or_default();
+
+        format!(r#"{compare}<div class="row">{charts}</div>"#)
+    }
+}
+
+// Progression charts for the first 10 people in a ranking
+pub fn top_ten_tab<'a>(ranked: impl Iterator<Item = &'a Cuber>, event: Event, comp_dates: &FxHashMap<String, Date>, comp_names: &FxHashMap<String, String>) -> ChartTab {
+    ChartTab {
+        label: "Top 10 Progression".to_string(),
+        event,
+        charts: ranked
+            .take(10)
+            .enumerate()
+            .map(|(i, c)| Chart {
+                title: format!("{}. {}", i + 1, c.name),
+                link: Some(format!("{}.html", profile_page_name(&c.id))),
+                points: points(c, event, comp_dates, comp_names),
+            })
+            .collect(),
+        compare: None,
+    }
+}
+
+// A tab per event the person has competed in
+pub fn profile_page(cuber: &Cuber, comp_dates: &FxHashMap<String, Date>, comp_names: &FxHashMap<String, String>) -> RankPage<ChartTab> {
+    // Only the first tab links to head to head, since it's for the person rather than any one event
+    let tables = Event::iter()
+        .map(|e| (*e, points(cuber, *e, comp_dates, comp_names)))
+        .filter(|(_, points)| !points.is_empty())
+        .enumerate()
+        .map(|(i, (event, points))| ChartTab {
+            label: event.to_nice_str().to_string(),
+            event,
+            charts: vec![Chart {
+                title: format!("Single {} / Average {}", cuber.get_single(event), cuber.get_average(event)),
+                link: None,
+                points,
+            }],
+            compare: (i == 0).then(|| cuber.id.clone()),
+        })
+        .collect();
+
+    RankPage {
+        name: profile_page_name(&cuber.id),
+        title: cuber.name.clone(),
+        tables,
+        selector: None,
+    }
+}
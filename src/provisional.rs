@@ -0,0 +1,238 @@
+use rustc_hash::FxHashSet;
+use serde::Deserialize;
+use crate::{Date, Event, WCACompetition, WCAResult};
+use crate::wcif::Wcif;
+
+// Results from a competition that isn't in the export yet, read from its WCIF or a WCA Live dump.
+// These are always treated as WA competitions.
+pub struct ProvisionalComp {
+    pub competition: WCACompetition,
+    pub results: Vec<WCAResult>,
+}
+
+// The competition part of WCA Live's GraphQL API response, e.g. for
+// competition(id) { wcaId name startDate endDate competitionEvents { event { id } rounds { number format { id }
+//   results { ranking best average attempts { result } person { name wcaId } } } } }
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LiveCompetition {
+    wca_id: String,
+    name: String,
+    start_date: String,
+    end_date: String,
+    competition_events: Vec<LiveEvent>,
+}
+
+#[derive(Deserialize)]
+struct LiveEvent {
+    event: LiveId,
+    rounds: Vec<LiveRound>,
+}
+
+#[derive(Deserialize)]
+struct LiveId {
+    id: String,
+}
+
+#[derive(Deserialize)]
+struct LiveRound {
+    format: Option<LiveId>,
+    #[serde(default)]
+    results: Vec<LiveResult>,
+}
+
+#[derive(Deserialize)]
+struct LiveResult {
+    ranking: Option<usize>,
+    best: isize,
+    average: isize,
+    #[serde(default)]
+    attempts: Vec<LiveAttempt>,
+    person: LivePerson,
+}
+
+#[derive(Deserialize)]
+struct LiveAttempt {
+    result: isize,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LivePerson {
+    name: String,
+    wca_id: Option<String>,
+}
+
+// One person's result in one round, before it's turned into an export row
+struct RoundResult<'a> {
+    name: &'a str,
+    wca_id: &'a str,
+    pos: Option<usize>,
+    best: isize,
+    average: isize,
+    attempts: Vec<isize>,
+}
+
+// The export numbers rounds 1, 2, 3 and calls the last one "f"
+fn round_type(index: usize, rounds: usize) -> String {
+    if index + 1 == rounds { "f".to_string() } else { (index + 1).to_string() }
+}
+
+fn to_row(comp_id: &str, event_id: &str, round_type: &str, format: &str, r: RoundResult) -> WCAResult {
+    let value = |i: usize| r.attempts.get(i).copied().unwrap_or(0).to_string();
+    WCAResult {
+        competitionId: comp_id.to_string(),
+        eventId: event_id.to_string(),
+        roundTypeId: round_type.to_string(),
+        pos: r.pos.map(|p| p.to_string()).unwrap_or_default(),
+        best: r.best.to_string(),
+        average: r.average.to_string(),
+        personName: r.name.to_string(),
+        personId: r.wca_id.to_string(),
+        formatId: format.to_string(),
+        value1: value(0),
+        value2: value(1),
+        value3: value(2),
+        value4: value(3),
+        value5: value(4),
+        regionalSingleRecord: String::new(),
+        regionalAverageRecord: String::new(),
+        personCountryId: String::new(),
+    }
+}
+
+fn to_competition(id: &str, name: &str, start: Date, end: Date, events: &[String]) -> WCACompetition {
+    WCACompetition {
+        id: id.to_string(),
+        name: name.to_string(),
+        cityName: "Western Australia".to_string(), // Anything fed in here is assumed to be a WA comp
+        countryId: "Australia".to_string(),
+        information: String::new(),
+        venue: String::new(),
+        venueAddress: String::new(),
+        venueDetails: String::new(),
+        external_website: String::new(),
+        cellName: name.to_string(),
+        latitude: String::new(),
+        longitude: String::new(),
+        cancelled: "0".to_string(),
+        eventSpecs: events.join(" "),
+        wcaDelegate: String::new(),
+        organiser: String::new(),
+        year: start.year.to_string(),
+        month: start.month.to_string(),
+        day: start.day.to_string(),
+        endMonth: end.month.to_string(),
+        endDay: end.day.to_string(),
+    }
+}
+
+impl ProvisionalComp {
+    // Reads either a WCIF file with results in it or a WCA Live dump (with or without GraphQL's "data" wrapper)
+    pub fn load(path: &str) -> Result<Self, String> {
+        let contents = std::fs::read_to_string(path).map_err(|e| format!("Couldn't read {path}: {e}"))?;
+        let json = serde_json::from_str::<serde_json::Value>(&contents).map_err(|e| format!("Couldn't parse {path}: {e}"))?;
+
+        let comp = if json.get("persons").is_some() {
+            let wcif = serde_json::from_value::<Wcif>(json).map_err(|e| format!("Couldn't parse {path} as WCIF: {e}"))?;
+            Self::from_wcif(&wcif)
+        }
+        else {
+            let live = json.pointer("/data/competition").or(json.get("competition")).unwrap_or(&json);
+            let live = serde_json::from_value::<LiveCompetition>(live.clone()).map_err(|e| format!("Couldn't parse {path} as WCA Live results: {e}"))?;
+            Self::from_live(&live)
+        };
+
+        comp.ok_or(format!("{path} is missing the competition's dates"))
+    }
+
+    fn from_wcif(wcif: &Wcif) -> Option<Self> {
+        let competition = to_competition(
+            &wcif.id,
+            &wcif.name,
+            wcif.start_date()?,
+            wcif.end_date()?,
+            &wcif.events.iter().map(|e| e.id.clone()).collect::<Vec<_>>()
+        );
+
+        let mut results = Vec::new();
+        for event in wcif.events.iter() {
+            for (i, round) in event.rounds.iter().enumerate() {
+                let round_type = round_type(i, event.rounds.len());
+                for r in round.results.iter() {
+                    // Newcomers don't have a WCA ID until the export does, so they have to wait
+                    let Some(person) = wcif.persons.iter().find(|p| p.registrant_id == Some(r.person_id)) else { continue };
+                    let Some(wca_id) = person.wca_id.as_ref() else { continue };
+
+                    results.push(to_row(&wcif.id, &event.id, &round_type, &round.format, RoundResult {
+                        name: &person.name,
+                        wca_id,
+                        pos: r.ranking,
+                        best: r.best,
+                        average: r.average,
+                        attempts: r.attempts.iter().map(|a| a.result).collect(),
+                    }));
+                }
+            }
+        }
+
+        Some(ProvisionalComp { competition, results })
+    }
+
+    fn from_live(live: &LiveCompetition) -> Option<Self> {
+        let competition = to_competition(
+            &live.wca_id,
+            &live.name,
+            Date::parse(&live.start_date)?,
+            Date::parse(&live.end_date)?,
+            &live.competition_events.iter().map(|e| e.event.id.clone()).collect::<Vec<_>>()
+        );
+
+        let mut results = Vec::new();
+        for event in live.competition_events.iter() {
+            for (i, round) in event.rounds.iter().enumerate() {
+                let round_type = round_type(i, event.rounds.len());
+                let format = round.format.as_ref().map_or("a", |f| f.id.as_str());
+                for r in round.results.iter() {
+                    let Some(wca_id) = r.person.wca_id.as_ref() else { continue };
+
+                    results.push(to_row(&live.wca_id, &event.event.id, &round_type, format, RoundResult {
+                        name: &r.person.name,
+                        wca_id,
+                        pos: r.ranking,
+                        best: r.best,
+                        average: r.average,
+                        attempts: r.attempts.iter().map(|a| a.result).collect(),
+                    }));
+                }
+            }
+        }
+
+        Some(ProvisionalComp { competition, results })
+    }
+}
+
+// Which pages were built with provisional results in them, so they can be marked as such
+#[derive(Default)]
+pub struct Provisional {
+    years: FxHashSet<usize>,
+    events: FxHashSet<usize>,
+}
+
+impl Provisional {
+    pub fn new(comps: &[ProvisionalComp]) -> Self {
+        Provisional {
+            years: comps.iter().filter_map(|c| c.competition.year.parse().ok()).collect(),
+            events: comps.iter().flat_map(|c| c.results.iter().map(|r| Event::str_to_index(&r.eventId))).collect(),
+        }
+    }
+
+    // A page is affected unless it's for an event or year the provisional comps didn't have
+    pub fn title(&self, title: String, event: Option<Event>, year: Option<usize>) -> String {
+        let affected = !self.events.is_empty()
+            && event.is_none_or(|e| self.events.contains(&(e as usize)))
+            && year.is_none_or(|y| self.years.contains(&y));
+
+        if affected { format!("{title} (Provisional)") } else { title }
+    }
+}
@@ -0,0 +1,125 @@
+use rustc_hash::{FxHashMap, FxHashSet};
+use crate::{Cuber, Date, Event, EventRanks, JsonCell, RankPage, RankRow, RankTable, ResultValue, Table, ToHtml, WCAResult, rank};
+use crate::movement::Movement;
+use crate::wca_ranks::WCARanks;
+use crate::wcif::Wcif;
+
+#[derive(Clone, Copy)]
+pub enum PsychCell {
+    Result(ResultValue),
+    Rank(Option<usize>),
+}
+
+impl ToHtml for PsychCell {
+    fn to_html_string(&self) -> String {
+        match self {
+            PsychCell::Result(r) => r.to_html_string(),
+            PsychCell::Rank(r) => r.to_html_string(),
+        }
+    }
+}
+
+impl JsonCell for PsychCell {
+    fn raw(&self) -> serde_json::Value {
+        match self {
+            PsychCell::Result(r) => r.raw(),
+            PsychCell::Rank(r) => r.raw(),
+        }
+    }
+
+    fn raw_csv(&self) -> Option<String> {
+        match self {
+            PsychCell::Result(r) => r.raw_csv(),
+            PsychCell::Rank(_) => None,
+        }
+    }
+}
+
+// Like the WCA's psych sheets, blind events and multi are seeded by single
+pub fn seeded_by_single(event: Event) -> bool {
+    matches!(event, Event::_3bld | Event::_4bld | Event::_5bld | Event::_3mbld)
+}
+
+// Everyone registered for the comp, with their results from the export (if they have any).
+// People without a WCA id yet are newcomers, so they just have a name. Visitors get a last competed date from their
+// results anywhere, like residents, so the active filter doesn't hide them.
+pub fn registrants(wcif: &Wcif, comp_dates: &FxHashMap<String, Date>) -> Result<Vec<(Cuber, Vec<String>)>, csv::Error> {
+    let ids = wcif.registrants().filter_map(|(p, _)| p.wca_id.clone()).collect::<FxHashSet<_>>();
+
+    let mut results = FxHashMap::<String, Vec<WCAResult>>::default();
+    for r in WCAResult::Read()?.filter_map(|r| r.ok()).filter(|r| ids.contains(&r.personId)) {
+        results.entry(r.personId.clone()).or_default().push(r);
+    }
+
+    Ok(wcif.registrants().map(|(person, events)| {
+        let cuber = match person.wca_id.as_ref().and_then(|id| results.remove(id)) {
+            Some(r) => {
+                let cuber = Cuber::new(r);
+                Cuber { last_competed: cuber.competition_dates(comp_dates).last().copied(), ..cuber }
+            },
+            None => Cuber { 
+                name: person.name.clone(), 
+                id: person.wca_id.clone().unwrap_or_default(), 
+                ..Default::default() 
+            },
+        };
+        (cuber, events.clone())
+    }).collect())
+}
+
+pub fn psych_sheet_page<'a>(wcif: &Wcif, registrants: &'a [(Cuber, Vec<String>)], wa_cubers: &[Cuber], wca_ranks: &WCARanks) -> RankPage<RankTable<'a, ResultValue, PsychCell, 3>> {
+    let tables = wcif.events
+        .iter()
+        .filter_map(|e| Event::from_id(&e.id).map(|event| (e, event)))
+        .map(|(wcif_event, event)| {
+            let by_single = seeded_by_single(event);
+
+            // Residents' WA ranks for whichever result the event is seeded by
+            let wa = EventRanks::new(wa_cubers, event);
+            let wa_ranks = FxHashMap::from_iter(
+                if by_single { &wa.single } else { &wa.average }
+                .iter()
+                .filter(|r| r.score.valid())
+                .map(|r| (r.person.id.as_str(), r.rank))
+            );
+
+            let mut rows = registrants
+                .iter()
+                .filter(|(_, events)| events.contains(&wcif_event.id))
+                .map(|(c, _)| {
+                    let (seed, other) = if by_single { 
+                        (c.get_single(event), c.get_average(event)) 
+                    } 
+                    else { 
+                        (c.get_average(event), c.get_single(event)) 
+                    };
+                    RankRow {
+                        rank: 0,
+                        score: seed,
+                        data: [
+                            PsychCell::Result(other),
+                            PsychCell::Rank(wa_ranks.get(c.id.as_str()).copied()),
+                            PsychCell::Rank(wca_ranks.world_rank(event, &c.id, !by_single)),
+                        ],
+                        person: c,
+                        movement: Movement::Unknown,
+                    }
+                })
+                .collect::<Vec<_>>();
+            rank(&mut rows, true);
+
+            RankTable {
+                label: event.to_nice_str().to_string(),
+                rows,
+                headers: [if by_single { "Average" } else { "Single" }, "WA Rank", "World Rank"],
+            }
+        })
+        .collect();
+
+    RankPage {
+        name: format!("psych_{}", wcif.id),
+        title: format!("{} Psych Sheet", wcif.name),
+        tables,
+        selector: None,
+    }
+}
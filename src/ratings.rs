@@ -0,0 +1,167 @@
+use std::collections::BTreeMap;
+use rustc_hash::{FxHashMap, FxHashSet};
+use crate::{Cuber, Date, Event, RankRow, RankTable, rank};
+use crate::movement::Movement;
+
+// Elo ratings per event. Every round at a WA comp is treated as a game between each pair of residents in it:
+// whoever placed higher wins (equal placings draw). After each round everyone's rating moves by
+//   k / (n - 1) * sum over opponents of (score - expected score)
+// where n is the number of residents in the round and expected score = 1 / (1 + 10^((their rating - yours) / 400)).
+// Dividing by n - 1 keeps a big first round from swinging ratings more than a small final.
+#[derive(Clone, Copy)]
+pub struct EloParams {
+    pub initial: f64, // Rating before someone's first round
+    pub k: f64,       // Most a rating can move in one round
+}
+
+impl Default for EloParams {
+    fn default() -> Self {
+        EloParams { initial: 1500.0, k: 32.0 }
+    }
+}
+
+#[derive(Clone)]
+pub struct Rating {
+    pub current: f64,
+    pub peak: f64,
+    pub rounds: usize,
+    pub history: Vec<(Date, f64)>, // Rating after each competition
+}
+
+// Rounds within a comp happen in this order (roundTypeId from the export)
+pub fn round_order(round_type: &str) -> usize {
+    match round_type {
+        "0" | "h"       => 0, // Qualification
+        "1" | "d"       => 1,
+        "2" | "e"       => 2,
+        "3" | "g"       => 3,
+        "b"             => 4,
+        "c" | "f"       => 5, // Final
+        _               => 6,
+    }
+}
+
+pub fn rate_event<'a>(cubers: &'a [Cuber], event: Event, wa_comp_ids: &FxHashSet<String>, comp_dates: &FxHashMap<String, Date>, params: EloParams) -> Vec<(&'a Cuber, Rating)> {
+    // (date, comp, round order) -> (index into cubers, placing)
+    let mut rounds = BTreeMap::<(Date, &str, usize), Vec<(usize, usize)>>::new();
+    for (i, cuber) in cubers.iter().enumerate() {
+        for r in cuber.results.iter() {
+            if Event::str_to_index(&r.eventId) != event as usize || !wa_comp_ids.contains(&r.competitionId) {
+                continue;
+            }
+            if let (Some(date), Ok(pos)) = (comp_dates.get(&r.competitionId), r.pos.parse::<usize>()) {
+                rounds
+                    .entry((*date, &r.competitionId, round_order(&r.roundTypeId)))
+                    .or_default()
+                    .push((i, pos));
+            }
+        }
+    }
+
+    let mut ratings = FxHashMap::<usize, Rating>::default();
+
+    for ((date, _, _), players) in rounds.into_iter().filter(|(_, players)| players.len() > 1) {
+        let before = players
+            .iter()
+            .map(|(i, _)| ratings.get(i).map_or(params.initial, |r| r.current))
+            .collect::<Vec<_>>();
+        let scale = params.k / (players.len() - 1) as f64;
+
+        for (a, (i, pos_a)) in players.iter().enumerate() {
+            let change = players
+                .iter()
+                .enumerate()
+                .filter(|(b, _)| *b != a)
+                .map(|(b, (_, pos_b))| {
+                    let expected = 1.0 / (1.0 + 10f64.powf((before[b] - before[a]) / 400.0));
+                    let score = match pos_a.cmp(pos_b) {
+                        std::cmp::Ordering::Less => 1.0,
+                        std::cmp::Ordering::Equal => 0.5,
+                        std::cmp::Ordering::Greater => 0.0,
+                    };
+                    score - expected
+                })
+                .sum::<f64>() * scale;
+
+            let rating = ratings.entry(*i).or_insert(Rating { current: params.initial, peak: params.initial, rounds: 0, history: Vec::new() });
+            rating.current = before[a] + change;
+            rating.peak = rating.peak.max(rating.current);
+            rating.rounds += 1;
+
+            // Only keep the rating at the end of each comp
+            if rating.history.last().is_some_and(|(d, _)| *d == date) {
+                rating.history.pop();
+            }
+            rating.history.push((date, rating.current));
+        }
+    }
+
+    ratings
+        .into_iter()
+        .map(|(i, rating)| (&cubers[i], rating))
+        .collect()
+}
+
+pub fn rating_table<'a>(ratings: &[(&'a Cuber, Rating)]) -> RankTable<'a, usize, usize, 2> {
+    let mut rows = ratings
+        .iter()
+        .map(|(c, r)| RankRow {
+            rank: 0,
+            score: r.current.round().max(0.0) as usize,
+            data: [r.peak.round().max(0.0) as usize, r.rounds],
+            person: c,
+            movement: Movement::Unknown,
+        })
+        .collect::<Vec<_>>();
+    rank(&mut rows, false);
+
+    RankTable {
+        label: "Rating".to_string(),
+        rows,
+        headers: ["Peak", "Rounds"],
+    }
+}
+
+// Person id -> [[date, rating], ...] for charting
+pub fn write_history(ratings: &[(&Cuber, Rating)], path: &str) -> std::io::Result<()> {
+    let history = BTreeMap::from_iter(
+        ratings
+        .iter()
+        .map(|(c, r)| (
+            c.id.as_str(), 
+            r.history.iter().map(|(d, v)| (d.to_string(), v.round() as isize)).collect::<Vec<_>>()
+        ))
+    );
+    std::fs::write(path, serde_json::to_string(&history)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::WCAResult;
+
+    // Ratings after one round between two people who both start at the default rating
+    fn one_round(pos_a: usize, pos_b: usize) -> (f64, f64) {
+        let cubers = [Cuber::new(vec![WCAResult::test("A", pos_a)]), Cuber::new(vec![WCAResult::test("B", pos_b)])];
+        let comps = FxHashSet::from_iter(["Test2024".to_string()]);
+        let dates = FxHashMap::from_iter([("Test2024".to_string(), Date { year: 2024, month: 6, day: 1 })]);
+
+        let ratings = rate_event(&cubers, Event::_3, &comps, &dates, EloParams::default());
+        let rating = |id: &str| ratings.iter().find(|(c, _)| c.id == id).map(|(_, r)| r.current).unwrap();
+        (rating("A"), rating("B"))
+    }
+
+    #[test]
+    fn evenly_matched_win_moves_half_of_k() {
+        let k = EloParams::default().k;
+        let (a, b) = one_round(1, 2);
+        assert!((a - (1500.0 + k / 2.0)).abs() < 1e-9, "{a}");
+        assert!((b - (1500.0 - k / 2.0)).abs() < 1e-9, "{b}");
+    }
+
+    #[test]
+    fn evenly_matched_tie_moves_nobody() {
+        let (a, b) = one_round(1, 1);
+        assert!((a - 1500.0).abs() < 1e-9 && (b - 1500.0).abs() < 1e-9, "{a} {b}");
+    }
+}
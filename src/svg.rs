@@ -0,0 +1,67 @@
+use crate::escape;
+
+// The parts of an inline SVG chart that the progression charts and histograms share: the size, the axes and their labels
+const WIDTH: f64 = 600.0;
+const RIGHT: f64 = 10.0;
+const BOTTOM: f64 = 25.0; // Room for the x axis labels
+
+pub struct Frame {
+    pub height: f64,
+    pub left: f64, // Room for the y axis labels
+    pub top: f64,  // Room for a legend or markers
+}
+
+impl Frame {
+    // Where value goes across the plot, for an x axis running from `from` to `to`
+    pub fn x(&self, value: f64, from: f64, to: f64) -> f64 {
+        self.left + (value - from) / (to - from) * (WIDTH - self.left - RIGHT)
+    }
+
+    // Where value goes down the plot, for a y axis running from `from` at the bottom to `to` at the top
+    pub fn y(&self, value: f64, from: f64, to: f64) -> f64 {
+        self.top + (to - value) / (to - from) * (self.height - self.top - BOTTOM)
+    }
+
+    // The x axis line
+    pub fn bottom(&self) -> f64 {
+        self.height - BOTTOM
+    }
+
+    // The opening <svg> tag and the axes. The caller adds the data and the closing tag.
+    pub fn open(&self, aria_label: &str) -> String {
+        let (height, left, top) = (self.height, self.left, self.top);
+        format!(
+            r#"<svg viewBox="0 0 {WIDTH} {height}" class="w-100" role="img" aria-label="{}" style="max-width: {WIDTH}px;" font-size="11"><path d="M {left} {top} V {} H {}" fill="none" stroke="currentColor" stroke-opacity="0.5"/>"#,
+            escape::attr(aria_label), self.bottom(), WIDTH - RIGHT
+        )
+    }
+
+    // Labels down the y axis, as (y, label)
+    pub fn y_labels(&self, labels: impl IntoIterator<Item = (f64, String)>) -> String {
+        distinct(labels, |y, label| format!(
+            r#"<text x="{}" y="{y:.1}" text-anchor="end" dominant-baseline="middle" fill="currentColor">{label}</text>"#,
+            self.left - 5.0
+        ))
+    }
+
+    // Labels along the x axis, as (x, label)
+    pub fn x_labels(&self, labels: impl IntoIterator<Item = (f64, String)>) -> String {
+        distinct(labels, |x, label| format!(
+            r#"<text x="{x:.1}" y="{}" text-anchor="middle" fill="currentColor">{label}</text>"#,
+            self.height - 8.0
+        ))
+    }
+}
+
+// Narrow ranges (e.g. multi points) can round to the same label more than once
+fn distinct(labels: impl IntoIterator<Item = (f64, String)>, text: impl Fn(f64, &str) -> String) -> String {
+    let mut svg = String::new();
+    let mut previous = String::new();
+    for (position, label) in labels {
+        if label != previous {
+            svg += &text(position, &escape::text(&label));
+        }
+        previous = label;
+    }
+    svg
+}
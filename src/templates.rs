@@ -0,0 +1,52 @@
+use std::sync::OnceLock;
+use minijinja::{Environment, Value};
+use serde::Serialize;
+
+// Built into the binary, so the site builds without a templates directory
+const DEFAULTS: [(&str, &str); 5] = [
+    ("page.html", include_str!("templates/page.html")),
+    ("homepage.html", include_str!("templates/homepage.html")),
+    ("rank_table.html", include_str!("templates/rank_table.html")),
+    ("tables.js", include_str!("templates/tables.js")),
+    ("tabs.js", include_str!("templates/tabs.js")),
+];
+
+static TEMPLATES: OnceLock<Environment<'static>> = OnceLock::new();
+
+// Any of the defaults can be replaced by a file with the same name in dir
+fn environment(dir: Option<&str>) -> Result<Environment<'static>, String> {
+    // Variables are HTML escaped (the default for .html templates) unless they're passed in with html()
+    let mut env = Environment::new();
+
+    for (name, default) in DEFAULTS {
+        let path = dir.map(|d| format!("{d}/{name}")).filter(|p| std::path::Path::new(p).exists());
+        let source = match path {
+            Some(path) => std::fs::read_to_string(&path).map_err(|e| format!("Couldn't read {path}: {e}"))?,
+            None => default.to_string(),
+        };
+        env.add_template_owned(name, source).map_err(|e| format!("Couldn't parse template {name}: {e}"))?;
+    }
+
+    Ok(env)
+}
+
+// Call before anything is rendered, otherwise the defaults are used
+pub fn load(dir: Option<&str>) -> Result<(), String> {
+    if let Some(dir) = dir.filter(|d| !std::path::Path::new(d).is_dir()) {
+        return Err(format!("Templates directory {dir} doesn't exist"));
+    }
+    TEMPLATES.set(environment(dir)?).map_err(|_| "Templates were already loaded".to_string())
+}
+
+// Already rendered HTML (e.g. a tab's table), which autoescaping leaves alone
+pub fn html(rendered: String) -> Value {
+    Value::from_safe_string(rendered)
+}
+
+// A template that fails to render (e.g. an override using a variable wrongly) stops the build, rather than leaving blank pages
+pub fn render<S: Serialize>(name: &str, context: S) -> Result<String, String> {
+    let env = TEMPLATES.get_or_init(|| environment(None).expect("Built-in templates should parse"));
+    env.get_template(name)
+        .and_then(|t| t.render(context))
+        .map_err(|e| format!("Couldn't render {name}: {e}"))
+}
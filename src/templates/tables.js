@@ -0,0 +1,39 @@
+// Search, the active filter and column sorting for every rank table on the page
+document.querySelectorAll(".rank-table").forEach((table) => {
+    const tools = table.previousElementSibling;
+    const search = tools.querySelector("[data-search]");
+    const active = tools.querySelector("[data-active-filter]");
+    const rows = Array.from(table.tBodies[0].rows);
+
+    // Dates are YYYY-MM-DD, so they compare as strings
+    const built = document.body.dataset.built;
+    const cutoff = (parseInt(built.slice(0, 4)) - 2) + built.slice(4);
+
+    const filter = () => {
+        const query = search.value.trim().toLowerCase();
+        rows.forEach((row) => {
+            row.hidden = !row.dataset.name.includes(query) || (active.checked && !(row.dataset.last >= cutoff));
+        });
+    };
+    search.addEventListener("input", filter);
+    active.addEventListener("change", filter);
+
+    // Click a column to sort by it, and again to reverse it. Blanks always go last.
+    const headers = table.tHead.querySelectorAll("th.sortable");
+    headers.forEach((th) => th.addEventListener("click", () => {
+        const column = th.cellIndex;
+        const ascending = th.getAttribute("aria-sort") !== "ascending";
+        headers.forEach((other) => other.setAttribute("aria-sort", "none"));
+        th.setAttribute("aria-sort", ascending ? "ascending" : "descending");
+
+        const key = (row) => row.cells[column].dataset.sort;
+        rows.sort((a, b) => {
+            const x = key(a), y = key(b);
+            if (x === "" || y === "") {
+                return (x === "") - (y === "");
+            }
+            return ascending ? x - y : y - x;
+        });
+        table.tBodies[0].append(...rows);
+    }));
+});
@@ -0,0 +1,19 @@
+// Keeps the open tab in the URL's hash, so a link (or a reload) goes straight to it, e.g. 333.html#average.
+// Panes are "<id>-pane" rather than "<id>", otherwise the browser would also scroll down to them.
+const showTab = (hash) => {
+    const button = hash && document.getElementById(decodeURIComponent(hash.slice(1)) + "-tab");
+    if (button && button.matches('[data-bs-toggle="tab"]') && !button.classList.contains("active")) {
+        button.click();
+    }
+};
+
+document.addEventListener("shown.bs.tab", (event) => {
+    history.replaceState(null, "", "#" + event.target.id.replace(/-tab$/, ""));
+});
+window.addEventListener("hashchange", () => showTab(location.hash));
+showTab(location.hash);
+
+// Switching page with the selector (e.g. to another year) keeps the same tab open, if the other page has it
+document.querySelectorAll(".dropdown-item").forEach((link) => {
+    link.addEventListener("click", () => { link.hash = location.hash; });
+});
@@ -0,0 +1,71 @@
+use rustc_hash::{FxHashMap, FxHashSet};
+use crate::{Event, RankRow, RankTable, ResultValue, Table, WCARankSingle, WCARankAverage};
+
+// (national, continental, world) rank for each person, one map per event
+type EventRanksById = Vec<FxHashMap<String, [Option<usize>; 3]>>;
+
+// Official ranks from the export, shown alongside the WA ranks on event pages
+#[derive(Default)]
+pub struct WCARanks {
+    single: EventRanksById,
+    average: EventRanksById,
+}
+
+impl WCARanks {
+    pub fn load(person_ids: &FxHashSet<String>) -> Result<Self, csv::Error> {
+        let mut ranks = WCARanks {
+            single: vec![FxHashMap::default(); 18],
+            average: vec![FxHashMap::default(); 18],
+        };
+
+        for r in WCARankSingle::Read()?.filter_map(|r| r.ok()).filter(|r| person_ids.contains(&r.personId)) {
+            Self::insert(&mut ranks.single, &r.eventId, &r.personId, [&r.countryRank, &r.continentRank, &r.worldRank]);
+        }
+
+        for r in WCARankAverage::Read()?.filter_map(|r| r.ok()).filter(|r| person_ids.contains(&r.personId)) {
+            Self::insert(&mut ranks.average, &r.eventId, &r.personId, [&r.countryRank, &r.continentRank, &r.worldRank]);
+        }
+
+        Ok(ranks)
+    }
+
+    fn insert(ranks: &mut EventRanksById, event_id: &str, person_id: &str, values: [&String; 3]) {
+        let idx = Event::str_to_index(event_id);
+        if idx != 18 {
+            // A rank of 0 means there isn't one (e.g. no continental ranking)
+            ranks[idx].insert(person_id.to_string(), values.map(|v| v.parse::<usize>().ok().filter(|r| *r > 0)));
+        }
+    }
+
+    pub fn world_rank(&self, event: Event, person_id: &str, average: bool) -> Option<usize> {
+        let ranks = if average { &self.average } else { &self.single };
+        ranks.get(event as usize)?.get(person_id)?[2]
+    }
+
+    pub fn single_table<'a>(&self, event: Event, label: &str, rows: Vec<RankRow<'a, ResultValue, usize, 0>>) -> RankTable<'a, ResultValue, Option<usize>, 3> {
+        Self::table(&self.single, event, label, rows)
+    }
+
+    pub fn average_table<'a>(&self, event: Event, label: &str, rows: Vec<RankRow<'a, ResultValue, usize, 0>>) -> RankTable<'a, ResultValue, Option<usize>, 3> {
+        Self::table(&self.average, event, label, rows)
+    }
+
+    fn table<'a>(ranks: &EventRanksById, event: Event, label: &str, rows: Vec<RankRow<'a, ResultValue, usize, 0>>) -> RankTable<'a, ResultValue, Option<usize>, 3> {
+        RankTable {
+            label: label.to_string(),
+            rows: rows
+                .into_iter()
+                .filter(|r| r.score.valid())
+                .map(|r| {
+                    let data = ranks
+                        .get(event as usize)
+                        .and_then(|e| e.get(&r.person.id))
+                        .copied()
+                        .unwrap_or([None; 3]);
+                    RankRow { rank: r.rank, score: r.score, data, person: r.person, movement: r.movement }
+                })
+                .collect(),
+            headers: ["NR", "CR", "WR"],
+        }
+    }
+}
@@ -0,0 +1,104 @@
+use serde::Deserialize;
+use crate::Date;
+
+// The parts of the WCA Competition Interchange Format (https://github.com/thewca/wcif) we use.
+// Everything else in the file is ignored.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Wcif {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub persons: Vec<WcifPerson>,
+    #[serde(default)]
+    pub events: Vec<WcifEvent>,
+    pub schedule: Option<WcifSchedule>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WcifPerson {
+    pub registrant_id: Option<usize>,
+    pub name: String,
+    pub wca_id: Option<String>,
+    pub registration: Option<WcifRegistration>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WcifRegistration {
+    #[serde(default)]
+    pub event_ids: Vec<String>,
+    pub status: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WcifEvent {
+    pub id: String,
+    #[serde(default)]
+    pub rounds: Vec<WcifRound>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WcifRound {
+    pub format: String, // "a" (average of 5), "m" (mean of 3), or "1"/"2"/"3" (best of)
+    pub advancement_condition: Option<WcifAdvancement>,
+    #[serde(default)]
+    pub results: Vec<WcifResult>, // Empty until the round has been held
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WcifResult {
+    pub person_id: usize, // The person's registrantId
+    pub ranking: Option<usize>,
+    #[serde(default)]
+    pub attempts: Vec<WcifAttempt>,
+    pub best: isize,
+    pub average: isize,
+}
+
+#[derive(Deserialize)]
+pub struct WcifAttempt {
+    pub result: isize, // Encoded like the export's values
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WcifAdvancement {
+    #[serde(rename = "type")]
+    pub kind: String, // "ranking", "percent" or "attemptResult"
+    pub level: isize,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WcifSchedule {
+    pub start_date: Option<String>,
+    pub number_of_days: Option<usize>,
+}
+
+impl Wcif {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let contents = std::fs::read_to_string(path).map_err(|e| format!("Couldn't read {path}: {e}"))?;
+        serde_json::from_str(&contents).map_err(|e| format!("Couldn't parse {path} as WCIF: {e}"))
+    }
+
+    pub fn start_date(&self) -> Option<Date> {
+        Date::parse(self.schedule.as_ref()?.start_date.as_ref()?)
+    }
+
+    pub fn end_date(&self) -> Option<Date> {
+        let days = self.schedule.as_ref()?.number_of_days.unwrap_or(1);
+        Some(self.start_date()?.plus_days(days.saturating_sub(1)))
+    }
+
+    // People whose registration has been accepted, and the events they're in
+    pub fn registrants(&self) -> impl Iterator<Item = (&WcifPerson, &Vec<String>)> {
+        self.persons
+            .iter()
+            .filter_map(|p| p.registration.as_ref().filter(|r| r.status == "accepted").map(|r| (p, &r.event_ids)))
+    }
+}
@@ -0,0 +1,66 @@
+use rustc_hash::FxHashMap;
+use crate::{JsonCell, RankRow, RankTable, ResultValue, ToHtml};
+
+// Extra columns on the yearly leaderboards, taken from the same ranking the year before
+#[derive(Clone, Copy)]
+pub enum LastYear {
+    Blank,
+    Rank(usize),
+    Result(ResultValue),
+}
+
+impl ToHtml for LastYear {
+    fn to_html_string(&self) -> String {
+        match self {
+            LastYear::Blank => "".to_string(),
+            LastYear::Rank(r) => r.to_string(),
+            LastYear::Result(r) => r.to_html_string(),
+        }
+    }
+}
+
+impl JsonCell for LastYear {
+    fn raw(&self) -> serde_json::Value {
+        match self {
+            LastYear::Blank => serde_json::Value::Null,
+            LastYear::Rank(r) => r.raw(),
+            LastYear::Result(r) => r.raw(),
+        }
+    }
+
+    fn raw_csv(&self) -> Option<String> {
+        match self {
+            LastYear::Result(r) => r.raw_csv(),
+            _ => None,
+        }
+    }
+}
+
+// Person id -> (rank, result) in one event's ranking for one year
+pub type YearResults = FxHashMap<String, (usize, ResultValue)>;
+
+pub fn year_results(rows: &[RankRow<ResultValue, usize, 0>]) -> YearResults {
+    FxHashMap::from_iter(
+        rows.iter()
+        .filter(|r| r.score.valid())
+        .map(|r| (r.person.id.clone(), (r.rank, r.score)))
+    )
+}
+
+pub fn year_table<'a>(label: &str, rows: Vec<RankRow<'a, ResultValue, usize, 0>>, last_year: Option<&YearResults>) -> RankTable<'a, ResultValue, LastYear, 2> {
+    RankTable {
+        label: label.to_string(),
+        rows: rows
+            .into_iter()
+            .filter(|r| r.score.valid())
+            .map(|r| {
+                let data = match last_year.and_then(|l| l.get(&r.person.id)) {
+                    Some((rank, result)) => [LastYear::Rank(*rank), LastYear::Result(*result)],
+                    None => [LastYear::Blank; 2],
+                };
+                RankRow { rank: r.rank, score: r.score, data, person: r.person, movement: r.movement }
+            })
+            .collect(),
+        headers: ["Last Year's Rank", "Last Year's Result"],
+    }
+}